@@ -0,0 +1,185 @@
+use crate::model::Config;
+use crate::scheduler::{latest_exams, latest_todo_list};
+use crate::utils::{
+    exams_to_ics, load_json, normalize_sessions, parse_classroom_schedule, save_json,
+    timetable_to_ics, todo_list_to_ics, update_ics_sequences, IcsSequence,
+};
+use crate::zju_assist::ZjuAssist;
+
+use log::info;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+
+static FEED_SERVER: Mutex<Option<tauri::async_runtime::JoinHandle<()>>> = Mutex::const_new(None);
+
+// keep SEQUENCE across exports so re-imports update the existing events
+pub fn load_ics_sequences(
+    handle: &AppHandle,
+    todo_list: &Vec<Value>,
+) -> Result<HashMap<String, IcsSequence>, String> {
+    let path = handle
+        .path()
+        .app_data_dir()
+        .map_err(|err| err.to_string())?
+        .join("ics_sequences.json");
+    let mut sequences: HashMap<String, IcsSequence> = load_json(&path).unwrap_or_default();
+    update_ics_sequences(&mut sequences, todo_list);
+    save_json(&path, &sequences)?;
+    Ok(sequences)
+}
+
+pub fn feed_url(config: &Config, name: &str) -> String {
+    format!(
+        "http://127.0.0.1:{}/{}?token={}",
+        config.calendar_feed_port, name, config.calendar_feed_token
+    )
+}
+
+async fn render_feed(handle: &AppHandle, config: &Config, name: &str) -> Option<String> {
+    match name {
+        "todos.ics" => {
+            let todo_list = latest_todo_list(handle).await.unwrap_or_default();
+            let sequences = load_ics_sequences(handle, &todo_list).ok()?;
+            Some(todo_list_to_ics(
+                &todo_list,
+                &config.ics_alarm_minutes,
+                &sequences,
+            ))
+        }
+        "timetable.ics" => {
+            let zju_assist = handle.state::<Arc<Mutex<ZjuAssist>>>().lock().await.clone();
+            let courses = zju_assist.get_courses().await.ok()?;
+            let mut sessions = courses.iter().flat_map(parse_classroom_schedule).collect();
+            normalize_sessions(&mut sessions, &config.campus_period_times);
+            Some(timetable_to_ics(&sessions, None))
+        }
+        "exams.ics" => {
+            let exams = latest_exams(handle).await.unwrap_or_default();
+            Some(exams_to_ics(&exams, &config.ics_alarm_minutes))
+        }
+        _ => None,
+    }
+}
+
+fn response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+// compare fixed-length digests byte by byte, so the time taken tells neither
+// the token length nor how much of it is right. an empty token never matches
+fn token_matches(expected: &str, given: &str) -> bool {
+    if expected.is_empty() {
+        return false;
+    }
+    Sha256::digest(expected.as_bytes())
+        .iter()
+        .zip(Sha256::digest(given.as_bytes()).iter())
+        .fold(0, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+async fn handle_connection(handle: &AppHandle, mut stream: TcpStream) -> std::io::Result<()> {
+    // only the request line is needed, the head is read up to 8 KiB
+    let mut buf = vec![0; 8192];
+    let mut len = 0;
+    while len < buf.len() {
+        let n = stream.read(&mut buf[len..]).await?;
+        if n == 0 {
+            break;
+        }
+        len += n;
+        if buf[..len].windows(4).any(|w| w == b"\r\n\r\n") {
+            break;
+        }
+    }
+    let head = String::from_utf8_lossy(&buf[..len]);
+    let mut parts = head.lines().next().unwrap_or("").split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let target = parts.next().unwrap_or("/");
+
+    let config = handle.state::<Arc<Mutex<Config>>>().lock().await.clone();
+    let res = match url::Url::parse(&format!("http://127.0.0.1{}", target)) {
+        _ if method != "GET" && method != "HEAD" => {
+            response("405 Method Not Allowed", "text/plain", "Method Not Allowed")
+        }
+        Err(_) => response("400 Bad Request", "text/plain", "Bad Request"),
+        Ok(url) => {
+            let token = url
+                .query_pairs()
+                .find(|(key, _)| key == "token")
+                .map(|(_, value)| value.to_string())
+                .unwrap_or_default();
+            let name = url.path().trim_start_matches('/');
+            if !token_matches(&config.calendar_feed_token, &token) {
+                response("401 Unauthorized", "text/plain", "Unauthorized")
+            } else {
+                match render_feed(handle, &config, name).await {
+                    Some(body) => response("200 OK", "text/calendar; charset=utf-8", &body),
+                    None => response("404 Not Found", "text/plain", "Not Found"),
+                }
+            }
+        }
+    };
+    let res = if method == "HEAD" {
+        // same headers without the body
+        let end = res.find("\r\n\r\n").map_or(res.len(), |i| i + 4);
+        res[..end].to_string()
+    } else {
+        res
+    };
+    stream.write_all(res.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+// (re)start the loopback server according to the current config, called on
+// startup and whenever the config is saved
+pub async fn restart_calendar_feed(handle: AppHandle) {
+    let mut server = FEED_SERVER.lock().await;
+    if let Some(task) = server.take() {
+        task.abort();
+        // wait for the listener to be dropped so the port can be bound again
+        let _ = task.await;
+    }
+
+    let config = handle.state::<Arc<Mutex<Config>>>().lock().await.clone();
+    if !config.calendar_feed_enabled {
+        return;
+    }
+    let listener = match TcpListener::bind(("127.0.0.1", config.calendar_feed_port)).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            info!("calendar_feed: bind failed {}", err);
+            return;
+        }
+    };
+    info!("calendar_feed: listening on {}", config.calendar_feed_port);
+    *server = Some(tauri::async_runtime::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    info!("calendar_feed: accept failed {}", err);
+                    continue;
+                }
+            };
+            let handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = handle_connection(&handle, stream).await {
+                    info!("calendar_feed: {}", err);
+                }
+            });
+        }
+    }));
+}
@@ -1,5 +1,23 @@
-use crate::model::{Config, Progress, Subject, Upload, VersionInfo};
-use crate::utils::{export_todo_ics, format_srt_timestamp, images_to_pdf, save_subtitle};
+use crate::calendar_feed::{feed_url, load_ics_sequences, restart_calendar_feed};
+use crate::model::{
+    ClassSession, Config, CourseHomework, CreditAudit, EvaluationStatus, Exam, GpaBreakdown,
+    GpaOptions, GpaSummary, NotifyChannel, Progress, QuizActivity, ScoreAnalytics, ScoreSnapshot,
+    SeatInfo, Subject, Topic, Upload, VersionInfo, VideoActivity,
+};
+use crate::scheduler::{
+    begin_sync_download, check_exam_changes, check_score_changes, check_seat_changes,
+    check_todo_changes, fetch_seats, finish_sync_download, latest_score, latest_seats,
+    load_course_categories, load_evaluation_status, notify_score_change, record_digest_uploads,
+    save_course_categories, send_digest, sync_caldav_todos,
+};
+use crate::utils::{
+    analytics_to_csv, annotate_score_items, audit_credits, course_categories, ding_channel,
+    exams_to_ics, export_todo_ics, format_srt_timestamp, generate_token, gpa_breakdown,
+    images_to_pdf, normalize_sessions, parse_classroom_schedule, parse_score_records,
+    quiz_to_markdown, quiz_to_pdf, save_subtitle, score_analytics, score_to_csv, score_to_pdf,
+    score_to_xlsx, send_email, send_notification, session_to_ics, session_uid, timetable_to_ics,
+    todo_exporter, topic_to_html, topic_to_markdown, CalDavClient,
+};
 use crate::zju_assist::{SubtitleContent, ZjuAssist};
 
 use chrono::{DateTime, Local, NaiveDate, Utc};
@@ -13,7 +31,11 @@ use serde_json::{json, Value};
 use std::cmp::min;
 use std::sync::atomic::AtomicBool;
 use std::time::Duration;
-use std::{path::Path, process::Command, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+};
 #[cfg(desktop)]
 use tauri::menu::{Menu, MenuItem, PredefinedMenuItem, Submenu};
 use tauri::path::BaseDirectory;
@@ -187,6 +209,7 @@ pub async fn logout(
 #[tauri::command]
 pub async fn sync_todo_once(
     zju_assist: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
     handle: AppHandle,
 ) -> Result<Vec<Value>, String> {
     info!("sync_todo_once");
@@ -195,6 +218,8 @@ pub async fn sync_todo_once(
         .get_todo_list()
         .await
         .map_err(|err| err.to_string())?;
+    let config = config.lock().await.clone();
+    check_todo_changes(&handle, &config, &todo_list).await;
     let todo_list_no_end_time = todo_list
         .iter()
         .filter(|todo| todo["end_time"].is_null())
@@ -317,6 +342,38 @@ pub async fn sync_todo_once(
                     None::<&str>,
                 )
                 .map_err(|err| err.to_string())?,
+                &MenuItem::with_id(
+                    &handle,
+                    "export-todo-md",
+                    "导出为 Markdown 清单",
+                    true,
+                    None::<&str>,
+                )
+                .map_err(|err| err.to_string())?,
+                &MenuItem::with_id(
+                    &handle,
+                    "export-todo-json",
+                    "导出为 JSON 文件",
+                    true,
+                    None::<&str>,
+                )
+                .map_err(|err| err.to_string())?,
+                &MenuItem::with_id(
+                    &handle,
+                    "export-todo-org",
+                    "导出为 Org-mode 文件",
+                    true,
+                    None::<&str>,
+                )
+                .map_err(|err| err.to_string())?,
+                &MenuItem::with_id(
+                    &handle,
+                    "export-todo-txt",
+                    "导出为 todo.txt 文件",
+                    true,
+                    None::<&str>,
+                )
+                .map_err(|err| err.to_string())?,
                 &PredefinedMenuItem::separator(&handle).map_err(|err| err.to_string())?,
                 &MenuItem::with_id(&handle, "export-todo-help", "查看帮助", true, None::<&str>)
                     .map_err(|err| err.to_string())?,
@@ -343,6 +400,38 @@ pub async fn sync_todo_once(
                     None::<&str>,
                 )
                 .map_err(|err| err.to_string())?,
+                &MenuItem::with_id(
+                    &handle,
+                    "export-todo-md",
+                    "导出为 Markdown 清单",
+                    true,
+                    None::<&str>,
+                )
+                .map_err(|err| err.to_string())?,
+                &MenuItem::with_id(
+                    &handle,
+                    "export-todo-json",
+                    "导出为 JSON 文件",
+                    true,
+                    None::<&str>,
+                )
+                .map_err(|err| err.to_string())?,
+                &MenuItem::with_id(
+                    &handle,
+                    "export-todo-org",
+                    "导出为 Org-mode 文件",
+                    true,
+                    None::<&str>,
+                )
+                .map_err(|err| err.to_string())?,
+                &MenuItem::with_id(
+                    &handle,
+                    "export-todo-txt",
+                    "导出为 todo.txt 文件",
+                    true,
+                    None::<&str>,
+                )
+                .map_err(|err| err.to_string())?,
                 &PredefinedMenuItem::separator(&handle).map_err(|err| err.to_string())?,
                 &MenuItem::with_id(&handle, "export-todo-help", "查看帮助", true, None::<&str>)
                     .map_err(|err| err.to_string())?,
@@ -385,9 +474,10 @@ pub async fn sync_todo_once(
 }
 
 #[tauri::command]
-pub fn export_todo(
+pub async fn export_todo(
     handle: AppHandle,
     window: Window,
+    config: State<'_, Arc<Mutex<Config>>>,
     todo_list: Vec<Value>,
     location: String,
 ) -> Result<(), String> {
@@ -411,6 +501,9 @@ pub fn export_todo(
 
         return Ok(());
     } else if location.starts_with("ics") {
+        let alarms = config.lock().await.ics_alarm_minutes.clone();
+        let sequences = load_ics_sequences(&handle, &todo_list)?;
+
         #[cfg(desktop)]
         window.set_focus().unwrap();
         #[cfg(desktop)]
@@ -425,7 +518,7 @@ pub fn export_todo(
                     Some(ics_path) => ics_path,
                     None => return,
                 };
-                let res = export_todo_ics(todo_list, &ics_path.to_string())
+                let res = export_todo_ics(todo_list, &ics_path.to_string(), &alarms, &sequences)
                     .map_err(|err| err.to_string());
                 match res {
                     Ok(_) => {
@@ -449,6 +542,45 @@ pub fn export_todo(
                 }
             });
         return Ok(());
+    } else if let Some(exporter) = todo_exporter(&location) {
+        #[cfg(desktop)]
+        window.set_focus().unwrap();
+        #[cfg(desktop)]
+        handle
+            .dialog()
+            .file()
+            .add_filter(exporter.name(), &[exporter.extension()])
+            .set_file_name("Todo")
+            .set_parent(&window)
+            .save_file(move |path| {
+                let path = match path {
+                    Some(path) => path,
+                    None => return,
+                };
+                let res = std::fs::write(path.to_string(), exporter.export(&todo_list))
+                    .map_err(|err| err.to_string());
+                match res {
+                    Ok(_) => {
+                        handle
+                            .notification()
+                            .builder()
+                            .title("导出待办事项成功")
+                            .body(&format!("文件已保存至：{}", path.to_string()))
+                            .show()
+                            .unwrap();
+                    }
+                    Err(err) => {
+                        handle
+                            .notification()
+                            .builder()
+                            .title("导出待办事项失败")
+                            .body(&err)
+                            .show()
+                            .unwrap();
+                    }
+                }
+            });
+        return Ok(());
     }
 
     #[cfg(target_os = "macos")]
@@ -645,6 +777,136 @@ pub async fn get_homework_uploads(
         .map_err(|err| err.to_string())
 }
 
+#[tauri::command]
+pub async fn get_quiz_activities(
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    course_id: i64,
+) -> Result<Vec<QuizActivity>, String> {
+    info!("get_quiz_activities: {}", course_id);
+    let zju_assist = state.lock().await.clone();
+    zju_assist
+        .get_quiz_activities(course_id)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn export_quizzes(
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
+    courses: Value,
+    format: String,
+) -> Result<Vec<String>, String> {
+    info!("export_quizzes: {}", format);
+    if format != "md" && format != "pdf" {
+        return Err("Invalid format".to_string());
+    }
+    let zju_assist = state.lock().await.clone();
+    let save_path = config.lock().await.save_path.clone();
+    let mut paths = Vec::new();
+    for course in courses.as_array().unwrap() {
+        let course_id = course["id"].as_i64().unwrap();
+        let course_name = course["name"].as_str().unwrap().replace("/", "-");
+        let quizzes = zju_assist
+            .get_quiz_activities(course_id)
+            .await
+            .map_err(|err| err.to_string())?;
+        let path = Path::new(&save_path).join(&course_name).join("quizzes");
+        std::fs::create_dir_all(&path).map_err(|err| err.to_string())?;
+        for quiz in quizzes {
+            let filepath = path
+                .join(format!(
+                    "{}-{}.{}",
+                    quiz.title.replace("/", "-"),
+                    quiz.id,
+                    format
+                ))
+                .to_str()
+                .unwrap()
+                .to_string();
+            if format == "md" {
+                std::fs::write(&filepath, quiz_to_markdown(&quiz))
+                    .map_err(|err| err.to_string())?;
+            } else {
+                quiz_to_pdf(&quiz, &filepath).map_err(|err| err.to_string())?;
+            }
+            debug!("export_quizzes: saved {}", filepath);
+            paths.push(filepath);
+        }
+    }
+    Ok(paths)
+}
+
+#[tauri::command]
+pub async fn get_course_topics(
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    course_id: i64,
+) -> Result<Vec<Topic>, String> {
+    info!("get_course_topics: {}", course_id);
+    let zju_assist = state.lock().await.clone();
+    zju_assist
+        .get_course_topics(course_id)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn export_course_topics(
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
+    courses: Value,
+    format: String,
+) -> Result<Vec<String>, String> {
+    info!("export_course_topics: {}", format);
+    let zju_assist = state.lock().await.clone();
+    let save_path = config.lock().await.save_path.clone();
+    let ext = match format.as_str() {
+        "md" => "md",
+        "html" => "html",
+        _ => return Err("Invalid format".to_string()),
+    };
+    let mut updated_paths = Vec::new();
+    for course in courses.as_array().unwrap() {
+        let course_id = course["id"].as_i64().unwrap();
+        let course_name = course["name"].as_str().unwrap().replace("/", "-");
+        let topics = zju_assist
+            .get_course_topics(course_id)
+            .await
+            .map_err(|err| err.to_string())?;
+        let path = Path::new(&save_path).join(&course_name).join("forum");
+        std::fs::create_dir_all(&path).map_err(|err| err.to_string())?;
+        for topic in topics {
+            let content = match ext {
+                "md" => topic_to_markdown(&topic),
+                _ => topic_to_html(&topic),
+            };
+            // files are keyed by the topic id, the title is only for reading
+            let prefix = format!("{}-", topic.id);
+            let file_name = format!("{}{}.{}", prefix, topic.title.replace("/", "-"), ext);
+            let filepath = path.join(&file_name);
+            // only rewrite topics that have new replies or edits since the last sync
+            if std::fs::read_to_string(&filepath).ok().as_deref() == Some(content.as_str()) {
+                continue;
+            }
+            std::fs::write(&filepath, content).map_err(|err| err.to_string())?;
+            // a renamed topic leaves the export under its old title behind
+            for entry in std::fs::read_dir(&path).map_err(|err| err.to_string())? {
+                let name = entry.map_err(|err| err.to_string())?.file_name();
+                let name = name.to_string_lossy();
+                if name != file_name
+                    && name.starts_with(&prefix)
+                    && name.ends_with(&format!(".{}", ext))
+                {
+                    let _ = std::fs::remove_file(path.join(name.as_ref()));
+                }
+            }
+            debug!("export_course_topics: updated {:?}", filepath);
+            updated_paths.push(filepath.to_str().unwrap().to_string());
+        }
+    }
+    Ok(updated_paths)
+}
+
 #[tauri::command]
 pub async fn download_file(
     state: State<'_, Arc<Mutex<ZjuAssist>>>,
@@ -663,6 +925,7 @@ pub async fn download_file(
 
 #[tauri::command]
 pub async fn get_uploads_list(
+    handle: AppHandle,
     state: State<'_, Arc<Mutex<ZjuAssist>>>,
     config: State<'_, Arc<Mutex<Config>>>,
     courses: Value,
@@ -670,7 +933,8 @@ pub async fn get_uploads_list(
 ) -> Result<Vec<Upload>, String> {
     info!("get_uploads_list: {}", sync_upload);
     let zju_assist = state.lock().await.clone();
-    let save_path = config.lock().await.save_path.clone();
+    let config = config.lock().await.clone();
+    let save_path = config.save_path.clone();
     let mut all_uploads = Vec::new();
     let mut tasks: Vec<JoinHandle<Result<Vec<Upload>, String>>> = Vec::new();
     for course in courses.as_array().unwrap() {
@@ -718,22 +982,237 @@ pub async fn get_uploads_list(
     }
 
     if sync_upload {
-        // remove files that already downloaded
-        let mut sync_uploads = Vec::new();
-        for upload in all_uploads.iter() {
-            let filepath = Path::new(&upload.path)
-                .join(&upload.file_name)
+        all_uploads = filter_synced_uploads(all_uploads);
+        if config.smtp_enabled && config.digest_frequency != "off" {
+            record_digest_uploads(&handle, &all_uploads).await;
+        }
+    }
+
+    Ok(all_uploads)
+}
+
+// remove files that already downloaded
+fn filter_synced_uploads(uploads: Vec<Upload>) -> Vec<Upload> {
+    let mut sync_uploads = Vec::new();
+    for upload in uploads.iter() {
+        let filepath = Path::new(&upload.path)
+            .join(&upload.file_name)
+            .to_str()
+            .unwrap()
+            .to_string();
+        // if path not exists, or size not match, then download
+        if !Path::new(&filepath).exists()
+            || Path::new(&filepath).metadata().unwrap().len() != upload.size as u64
+        {
+            sync_uploads.push(upload.clone());
+        }
+    }
+    sync_uploads
+}
+
+#[tauri::command]
+pub async fn get_homework_dashboard(
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    courses: Value,
+) -> Result<Vec<CourseHomework>, String> {
+    info!("get_homework_dashboard");
+    let zju_assist = state.lock().await.clone();
+    let user_id = zju_assist
+        .get_user_id()
+        .await
+        .map_err(|err| err.to_string())?;
+    let mut dashboard = Vec::new();
+    let mut tasks: Vec<JoinHandle<Result<CourseHomework, String>>> = Vec::new();
+    for course in courses.as_array().unwrap() {
+        let course_id = course["id"].as_i64().unwrap();
+        let course_name = course["name"].as_str().unwrap().to_string();
+        let zju_assist = zju_assist.clone();
+        tasks.push(tokio::task::spawn(async move {
+            let homeworks = zju_assist
+                .get_homework_status(course_id, user_id)
+                .await
+                .map_err(|err| err.to_string())?;
+            Ok(CourseHomework {
+                course_id,
+                course_name,
+                homeworks,
+            })
+        }));
+    }
+
+    for task in tasks {
+        dashboard.push(task.await.map_err(|err| err.to_string())??);
+    }
+
+    Ok(dashboard)
+}
+
+#[tauri::command]
+pub async fn get_homework_feedback_uploads(
+    handle: AppHandle,
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
+    courses: Value,
+    sync_upload: bool,
+) -> Result<Vec<Upload>, String> {
+    info!("get_homework_feedback_uploads: {}", sync_upload);
+    let config = config.lock().await.clone();
+    let save_path = config.save_path.clone();
+    let dashboard = get_homework_dashboard(state, courses).await?;
+    let mut all_uploads = Vec::new();
+    for course in dashboard {
+        let course_name = course.course_name.replace("/", "-");
+        for homework in course.homeworks {
+            // teacher's files are saved next to our own submissions, the ids keep
+            // same-titled homeworks apart
+            let path = Path::new(&save_path)
+                .join(&course_name)
+                .join("submissions")
+                .join(format!(
+                    "{}-{}-{}",
+                    course.course_id,
+                    homework.id,
+                    homework.title.replace("/", "-")
+                ))
+                .join("feedback")
                 .to_str()
                 .unwrap()
                 .to_string();
-            // if path not exists, or size not match, then download
-            if !Path::new(&filepath).exists()
-                || Path::new(&filepath).metadata().unwrap().len() != upload.size as u64
-            {
-                sync_uploads.push(upload.clone());
+            // entries without ids can't be downloaded, skip them
+            all_uploads.extend(homework.feedback_uploads.iter().filter_map(|upload| {
+                Some(Upload {
+                    id: upload["id"].as_i64()?,
+                    reference_id: upload["reference_id"].as_i64()?,
+                    file_name: upload["name"].as_str()?.to_string(),
+                    course_name: course_name.clone(),
+                    path: path.clone(),
+                    size: upload["size"].as_u64().unwrap_or(1000),
+                })
+            }));
+        }
+    }
+
+    if sync_upload {
+        all_uploads = filter_synced_uploads(all_uploads);
+        if config.smtp_enabled && config.digest_frequency != "off" {
+            record_digest_uploads(&handle, &all_uploads).await;
+        }
+    }
+
+    Ok(all_uploads)
+}
+
+#[tauri::command]
+pub async fn get_submission_uploads_list(
+    handle: AppHandle,
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
+    courses: Value,
+    sync_upload: bool,
+) -> Result<Vec<Upload>, String> {
+    info!("get_submission_uploads_list: {}", sync_upload);
+    let zju_assist = state.lock().await.clone();
+    let config = config.lock().await.clone();
+    let save_path = config.save_path.clone();
+    let user_id = zju_assist
+        .get_user_id()
+        .await
+        .map_err(|err| err.to_string())?;
+    let mut all_uploads = Vec::new();
+    let mut tasks: Vec<JoinHandle<Result<Vec<Upload>, String>>> = Vec::new();
+    for course in courses.as_array().unwrap() {
+        let course_id = course["id"].as_i64().unwrap();
+        let course_name = course["name"].as_str().unwrap().replace("/", "-");
+        debug!(
+            "get_submission_uploads_list: course - {} {}",
+            course_id, course_name
+        );
+        let zju_assist = zju_assist.clone();
+        let save_path = save_path.clone();
+        tasks.push(tokio::task::spawn(async move {
+            let mut uploads = Vec::new();
+            let homeworks = zju_assist
+                .get_homework_activities(course_id)
+                .await
+                .map_err(|err| err.to_string())?;
+            for homework in homeworks {
+                let activity_id = match homework["id"].as_i64() {
+                    Some(activity_id) => activity_id,
+                    None => continue,
+                };
+                // same folder as the teacher's feedback of this homework
+                let folder = format!(
+                    "{}-{}-{}",
+                    course_id,
+                    activity_id,
+                    homework["title"].as_str().unwrap_or("").replace("/", "-")
+                );
+                let submissions = zju_assist
+                    .get_homework_submissions(activity_id, user_id)
+                    .await
+                    .map_err(|err| err.to_string())?;
+                for submission in submissions {
+                    // each submission gets its own folder, so a re-run only fetches new ones
+                    let submission_key = submission["submitted_at"]
+                        .as_str()
+                        .or(submission["created_at"].as_str())
+                        .and_then(|time| time.parse::<DateTime<Utc>>().ok())
+                        .map(|time| {
+                            time.with_timezone(&Local)
+                                .format("%Y%m%d-%H%M%S")
+                                .to_string()
+                        })
+                        .unwrap_or(submission["id"].as_i64().unwrap_or(0).to_string());
+                    let path = Path::new(&save_path)
+                        .join(&course_name)
+                        .join("submissions")
+                        .join(&folder)
+                        .join(&submission_key)
+                        .to_str()
+                        .unwrap()
+                        .to_string();
+                    for upload in submission["uploads"].as_array().unwrap_or(&Vec::new()) {
+                        // entries without ids can't be downloaded, skip them
+                        let (id, reference_id, file_name) = match (
+                            upload["id"].as_i64(),
+                            upload["reference_id"].as_i64(),
+                            upload["name"].as_str(),
+                        ) {
+                            (Some(id), Some(reference_id), Some(file_name)) => {
+                                (id, reference_id, file_name.to_string())
+                            }
+                            _ => continue,
+                        };
+                        let size = upload["size"].as_u64().unwrap_or(1000);
+                        debug!(
+                            "get_submission_uploads_list: uploads - {} {} {} {} {}",
+                            id, reference_id, file_name, path, size
+                        );
+                        uploads.push(Upload {
+                            id,
+                            reference_id,
+                            file_name,
+                            course_name: course_name.clone(),
+                            path: path.clone(),
+                            size,
+                        });
+                    }
+                }
             }
+            Ok(uploads)
+        }));
+    }
+
+    for task in tasks {
+        let uploads = task.await.map_err(|err| err.to_string())??;
+        all_uploads.extend(uploads);
+    }
+
+    if sync_upload {
+        all_uploads = filter_synced_uploads(all_uploads);
+        if config.smtp_enabled && config.digest_frequency != "off" {
+            record_digest_uploads(&handle, &all_uploads).await;
         }
-        all_uploads = sync_uploads;
     }
 
     Ok(all_uploads)
@@ -743,6 +1222,7 @@ pub async fn get_uploads_list(
 pub async fn start_download_upload(
     zju_assist: State<'_, Arc<Mutex<ZjuAssist>>>,
     state: State<'_, DashMap<String, Arc<AtomicBool>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
     window: Window,
     id: String,
     upload: Upload,
@@ -822,8 +1302,13 @@ pub async fn start_download_upload(
     let mut file = tokio::fs::File::create(filepath.clone())
         .await
         .map_err(|e| e.to_string())?;
+    let config = config.lock().await.clone();
+    if sync_upload {
+        begin_sync_download().await;
+    }
 
     tokio::task::spawn(async move {
+        let mut downloaded = None;
         let mut current_size: u64 = 0;
         let mut stream = res.bytes_stream();
         loop {
@@ -877,10 +1362,14 @@ pub async fn start_download_upload(
                 if let Err(err) = res {
                     debug!("download_upload: clean up fail: {}", err);
                 }
+                if sync_upload {
+                    finish_sync_download(&config, None).await;
+                }
                 return;
             }
             let item = res.unwrap();
             if item.is_none() {
+                downloaded = Some(format!("{}：{}", upload.course_name, file_name));
                 break;
             }
             let chunk = item.unwrap();
@@ -945,6 +1434,10 @@ pub async fn start_download_upload(
             "download_upload: done {} {} {} {}",
             upload.id, upload.reference_id, upload.file_name, upload.path
         );
+        // only new uploads found by the sync are worth a remote notification
+        if sync_upload {
+            finish_sync_download(&config, downloaded).await;
+        }
     });
 
     Ok(())
@@ -1639,16 +2132,149 @@ pub async fn start_download_playback(
         "download_playback: stream {} {} {:?}",
         subject.course_name, subject.sub_name, filepath
     );
-    let mut file = tokio::fs::File::create(filepath.clone())
-        .await
-        .map_err(|e| e.to_string())?;
+    spawn_stream_download(window, id, res, filepath, content_length, download_state).await
+}
 
-    tokio::task::spawn(async move {
-        let mut current_size: u64 = 0;
-        let mut stream = res.bytes_stream();
-        loop {
-            let res = stream.try_next().await.map_err(|e| e.to_string());
-            if let Err(err) = res {
+#[tauri::command]
+pub async fn get_video_activities_list(
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
+    courses: Value,
+    sync_upload: bool,
+) -> Result<Vec<VideoActivity>, String> {
+    info!("get_video_activities_list: {}", sync_upload);
+    let zju_assist = state.lock().await.clone();
+    let save_path = config.lock().await.save_path.clone();
+    let mut all_videos = Vec::new();
+    let mut tasks: Vec<JoinHandle<Result<Vec<VideoActivity>, String>>> = Vec::new();
+    for course in courses.as_array().unwrap() {
+        let course_id = course["id"].as_i64().unwrap();
+        let course_name = course["name"].as_str().unwrap().replace("/", "-");
+        let zju_assist = zju_assist.clone();
+        let save_path = save_path.clone();
+        tasks.push(tokio::task::spawn(async move {
+            let videos = zju_assist
+                .get_video_activities(course_id)
+                .await
+                .map_err(|err| err.to_string())?;
+            let path = Path::new(&save_path)
+                .join(&course_name)
+                .to_str()
+                .unwrap()
+                .to_string();
+            Ok(videos
+                .into_iter()
+                .map(|video| VideoActivity {
+                    course_name: course_name.clone(),
+                    path: path.clone(),
+                    ..video
+                })
+                .collect())
+        }));
+    }
+
+    for task in tasks {
+        let videos = task.await.map_err(|err| err.to_string())??;
+        all_videos.extend(videos);
+    }
+
+    if sync_upload {
+        // the size of external media is unknown, only skip those already on disk
+        all_videos.retain(|video| {
+            let filepath = Path::new(&video.path).join(&video.file_name);
+            !filepath.exists()
+                || (video.size != 0 && filepath.metadata().unwrap().len() != video.size)
+        });
+    }
+
+    Ok(all_videos)
+}
+
+#[tauri::command]
+pub async fn start_download_video(
+    zju_assist: State<'_, Arc<Mutex<ZjuAssist>>>,
+    state: State<'_, DashMap<String, Arc<AtomicBool>>>,
+    window: Window,
+    id: String,
+    video: VideoActivity,
+    sync_upload: bool,
+) -> Result<(), String> {
+    info!("start_download_video: {} {}", id, video.file_name);
+
+    let zju_assist = zju_assist.lock().await.clone();
+    // state -> true: downloading, false: cancel
+    let download_state = Arc::new(AtomicBool::new(true));
+    state.insert(id.clone(), download_state.clone());
+
+    let res = zju_assist.get_video_response(&video.url).await;
+    let res = match res {
+        Ok(res) => res,
+        Err(err) => {
+            debug!(
+                "download_video: fail {} {} {}",
+                video.course_name, video.file_name, err
+            );
+            state.remove(&id);
+            return Err("下载失败".to_string());
+        }
+    };
+
+    // create father dir if not exists
+    std::fs::create_dir_all(Path::new(&video.path)).map_err(|e| e.to_string())?;
+
+    let content_length = res.content_length().unwrap_or(video.size);
+    let filepath = Path::new(&video.path).join(&video.file_name);
+
+    info!("download_video - filepath: {:?}", filepath);
+
+    // if path exists, and size match, then skip
+    if sync_upload && filepath.exists() && filepath.metadata().unwrap().len() == content_length {
+        window
+            .emit(
+                "download-progress",
+                Progress {
+                    id: id.clone(),
+                    status: "done".to_string(),
+                    file_name: video.file_name.clone(),
+                    downloaded_size: content_length,
+                    total_size: content_length,
+                    msg: "下载完成".to_string(),
+                },
+            )
+            .unwrap();
+        info!(
+            "download_video: done {} {} {}",
+            video.course_name, video.file_name, video.path
+        );
+        return Ok(());
+    }
+    debug!(
+        "download_video: stream {} {} {:?}",
+        video.course_name, video.file_name, filepath
+    );
+    spawn_stream_download(window, id, res, filepath, content_length, download_state).await
+}
+
+// stream the response body into filepath in background, emit progress and honor cancel
+async fn spawn_stream_download(
+    window: Window,
+    id: String,
+    res: reqwest::Response,
+    filepath: PathBuf,
+    content_length: u64,
+    download_state: Arc<AtomicBool>,
+) -> Result<(), String> {
+    let file_name = filepath.file_name().unwrap().to_string_lossy().to_string();
+    let mut file = tokio::fs::File::create(filepath.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    tokio::task::spawn(async move {
+        let mut current_size: u64 = 0;
+        let mut stream = res.bytes_stream();
+        loop {
+            let res = stream.try_next().await.map_err(|e| e.to_string());
+            if let Err(err) = res {
                 window
                     .emit(
                         "download-progress",
@@ -1662,16 +2288,13 @@ pub async fn start_download_playback(
                         },
                     )
                     .unwrap();
-                info!(
-                    "download_playback: fail {} {} {} {}",
-                    subject.course_name, subject.sub_name, subject.path, err
-                );
+                info!("stream_download: fail {:?} {}", filepath, err);
                 // clean up
                 let res = tokio::fs::remove_file(&filepath.clone())
                     .await
                     .map_err(|e| e.to_string());
                 if let Err(err) = res {
-                    debug!("download_playback: clean up fail: {}", err);
+                    debug!("stream_download: clean up fail: {}", err);
                 }
                 break;
             }
@@ -1695,7 +2318,7 @@ pub async fn start_download_playback(
                     .await
                     .map_err(|e| e.to_string());
                 if let Err(err) = res {
-                    debug!("download_playback: clean up fail: {}", err);
+                    debug!("stream_download: clean up fail: {}", err);
                 }
                 return;
             }
@@ -1720,16 +2343,13 @@ pub async fn start_download_playback(
                         },
                     )
                     .unwrap();
-                info!(
-                    "download_playback: fail {} {} {} {}",
-                    subject.course_name, subject.sub_name, subject.path, err
-                );
+                info!("stream_download: fail {:?} {}", filepath, err);
                 // clean up
                 let res = tokio::fs::remove_file(&filepath.clone())
                     .await
                     .map_err(|e| e.to_string());
                 if let Err(err) = res {
-                    debug!("download_playback: clean up fail: {}", err);
+                    debug!("stream_download: clean up fail: {}", err);
                 }
                 break;
             }
@@ -1761,10 +2381,7 @@ pub async fn start_download_playback(
                 },
             )
             .unwrap();
-        info!(
-            "download_playback: done {} {} {}",
-            subject.course_name, subject.sub_name, subject.path
-        );
+        info!("stream_download: done {:?}", filepath);
     });
 
     Ok(())
@@ -1994,17 +2611,250 @@ pub async fn check_evaluation_done(
     Ok(res)
 }
 
+// which courses are left and when the window closes, for the score page
+#[tauri::command]
+pub async fn get_evaluation_status(
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+) -> Result<EvaluationStatus, String> {
+    info!("get_evaluation_status");
+    let mut zju_assist = state.lock().await.clone();
+    load_evaluation_status(&mut zju_assist).await
+}
+
 #[tauri::command]
 pub async fn get_score(state: State<'_, Arc<Mutex<ZjuAssist>>>) -> Result<Vec<Value>, String> {
     info!("get_score");
     let mut zju_assist = state.lock().await;
-    let score = zju_assist
+    let mut score = zju_assist
         .get_score()
         .await
         .map_err(|err| err.to_string())?;
+    annotate_score_items(&mut score);
     Ok(score)
 }
 
+// fetch, diff against the saved snapshot and notify like the background watcher
+#[tauri::command]
+pub async fn sync_score(
+    handle: AppHandle,
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
+) -> Result<ScoreSnapshot, String> {
+    info!("sync_score");
+    let mut zju_assist = state.lock().await.clone();
+    let score = zju_assist
+        .get_score()
+        .await
+        .map_err(|err| err.to_string())?;
+    let config = config.lock().await.clone();
+    Ok(check_score_changes(&handle, &config, &zju_assist.get_username(), score).await)
+}
+
+// columns only apply to csv and xlsx, the pdf is a fixed transcript layout
+#[tauri::command]
+pub async fn export_score(
+    handle: AppHandle,
+    window: Window,
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    format: String,
+    columns: Option<Vec<String>>,
+) -> Result<(), String> {
+    info!("export_score: {}", format);
+    let mut zju_assist = state.lock().await.clone();
+    let score = zju_assist
+        .get_score()
+        .await
+        .map_err(|err| err.to_string())?;
+    let records = parse_score_records(&score);
+    let columns = columns.unwrap_or_default();
+    let (filter, content) = match format.as_str() {
+        "csv" => (
+            ("CSV", "csv"),
+            score_to_csv(&records, &columns).into_bytes(),
+        ),
+        "xlsx" => (("Excel", "xlsx"), score_to_xlsx(&records, &columns)?),
+        "pdf" => (
+            ("PDF", "pdf"),
+            score_to_pdf(&records, &zju_assist.get_username()),
+        ),
+        _ => return Err(format!("不支持的格式：{}", format)),
+    };
+
+    save_file_with_dialog(handle, &window, filter, "Score", "成绩", content);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_score_snapshot(handle: AppHandle) -> Result<Option<ScoreSnapshot>, String> {
+    info!("get_score_snapshot");
+    Ok(latest_score(&handle).await)
+}
+
+#[tauri::command]
+pub async fn get_gpa_breakdown(
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    options: Option<GpaOptions>,
+) -> Result<GpaBreakdown, String> {
+    info!("get_gpa_breakdown");
+    let mut zju_assist = state.lock().await.clone();
+    let score = zju_assist
+        .get_score()
+        .await
+        .map_err(|err| err.to_string())?;
+    let records = parse_score_records(&score);
+    Ok(gpa_breakdown(&records, &options.unwrap_or_default()))
+}
+
+#[tauri::command]
+pub async fn get_credit_audit(
+    handle: AppHandle,
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+) -> Result<CreditAudit, String> {
+    info!("get_credit_audit");
+    let mut zju_assist = state.lock().await.clone();
+    let plan = zju_assist
+        .get_training_plan()
+        .await
+        .map_err(|err| err.to_string())?;
+    // the plan alone still places most required courses
+    let completion = zju_assist
+        .get_plan_completion()
+        .await
+        .unwrap_or_else(|err| {
+            info!("get_credit_audit: get completion failed {}", err);
+            Vec::new()
+        });
+    let score = zju_assist
+        .get_score()
+        .await
+        .map_err(|err| err.to_string())?;
+    let records = parse_score_records(&score);
+    // kept for the category averages of the score analytics
+    save_course_categories(&handle, &course_categories(&plan, &completion)).await;
+    Ok(audit_credits(&plan, &completion, &records))
+}
+
+// figures come from the saved score snapshot, no request is made
+async fn load_score_analytics(handle: &AppHandle) -> Result<ScoreAnalytics, String> {
+    let snapshot = latest_score(handle)
+        .await
+        .ok_or("尚未同步成绩，请先同步成绩".to_string())?;
+    let records = parse_score_records(&snapshot.score);
+    let categories = load_course_categories(handle).await;
+    Ok(score_analytics(&records, &categories, &snapshot.synced_at))
+}
+
+#[tauri::command]
+pub async fn get_score_analytics(handle: AppHandle) -> Result<ScoreAnalytics, String> {
+    info!("get_score_analytics");
+    load_score_analytics(&handle).await
+}
+
+#[tauri::command]
+pub async fn export_score_analytics(
+    handle: AppHandle,
+    window: Window,
+    format: String,
+) -> Result<(), String> {
+    info!("export_score_analytics: {}", format);
+    let analytics = load_score_analytics(&handle).await?;
+    let (filter, content) = match format.as_str() {
+        "json" => (
+            ("JSON", "json"),
+            serde_json::to_vec_pretty(&analytics).map_err(|err| err.to_string())?,
+        ),
+        "csv" => (("CSV", "csv"), analytics_to_csv(&analytics).into_bytes()),
+        _ => return Err(format!("不支持的格式：{}", format)),
+    };
+
+    save_file_with_dialog(
+        handle,
+        &window,
+        filter,
+        "ScoreAnalytics",
+        "成绩分析",
+        content,
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_seat_snapshot(handle: AppHandle) -> Result<Vec<SeatInfo>, String> {
+    info!("get_seat_snapshot");
+    Ok(latest_seats(&handle).await)
+}
+
+// same rate limit as the background watcher
+#[tauri::command]
+pub async fn check_seats_now(
+    handle: AppHandle,
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
+) -> Result<Vec<SeatInfo>, String> {
+    info!("check_seats_now");
+    let config = config.lock().await.clone();
+    if config.seat_watch_xkkhs.is_empty() {
+        return Err("尚未添加要监控的选课课号".to_string());
+    }
+    let mut zju_assist = state.lock().await.clone();
+    let seats = fetch_seats(&mut zju_assist, &config.seat_watch_xkkhs).await?;
+    check_seat_changes(&handle, &config, &seats).await;
+    Ok(seats)
+}
+
+// year like "2024-2025" and term like "1|秋", both empty for all semesters
+#[tauri::command]
+pub async fn get_exams(
+    handle: AppHandle,
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
+    year: Option<String>,
+    term: Option<String>,
+) -> Result<Vec<Exam>, String> {
+    info!("get_exams: {:?} {:?}", year, term);
+    let year = year.unwrap_or_default();
+    let term = term.unwrap_or_default();
+    let mut zju_assist = state.lock().await.clone();
+    let exams = zju_assist
+        .get_exams(&year, &term)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    // the snapshot always holds all semesters
+    if year.is_empty() && term.is_empty() {
+        let config = config.lock().await.clone();
+        check_exam_changes(&handle, &config, &exams).await;
+    }
+    Ok(exams)
+}
+
+#[tauri::command]
+pub async fn export_exams_ics(
+    handle: AppHandle,
+    window: Window,
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
+) -> Result<(), String> {
+    info!("export_exams_ics");
+    let alarms = config.lock().await.ics_alarm_minutes.clone();
+    let mut zju_assist = state.lock().await.clone();
+    let exams = zju_assist
+        .get_exams("", "")
+        .await
+        .map_err(|err| err.to_string())?;
+    let content = exams_to_ics(&exams, &alarms);
+
+    save_file_with_dialog(
+        handle,
+        &window,
+        ("iCalendar", "ics"),
+        "Exams",
+        "考试安排",
+        content.into_bytes(),
+    );
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn notify_score(
     handle: AppHandle,
@@ -2014,84 +2864,67 @@ pub async fn notify_score(
     total_gp: f64,
     total_credit: f64,
     ding_url: String,
+    ding_secret: Option<String>,
 ) -> Result<(), String> {
     info!("notify_score");
-
-    // TODO: Add bkcj support
-
-    let xkkh = score["xkkh"].as_str().unwrap();
-    let kcmc = score["kcmc"].as_str().unwrap();
-    let cj = score["cj"].as_str().unwrap();
-    let xf = score["xf"].as_str().unwrap();
-    let jd = score["jd"].as_str().unwrap();
-
-    let old_gpa = if old_total_credit == 0.0 {
-        0.0
+    let channels = if ding_url.is_empty() {
+        vec![]
     } else {
-        old_total_gp / old_total_credit
+        vec![ding_channel(&ding_url, &ding_secret.unwrap_or_default())]
     };
-    let new_gpa = if total_credit == 0.0 {
-        0.0
-    } else {
-        total_gp / total_credit
+    let summary = |gp: f64, credit: f64| GpaSummary {
+        credit,
+        gpa: if credit == 0.0 { 0.0 } else { gp / credit },
+        ..Default::default()
     };
+    notify_score_change(
+        &handle,
+        &channels,
+        None,
+        &score,
+        &summary(old_total_gp, old_total_credit),
+        &summary(total_gp, total_credit),
+    )
+    .await
+}
 
-    if !ding_url.is_empty() {
-        let markdown_text = format!(
-            "### 考试成绩通知\n - **选课课号**\t{}\n - **课程名称**\t{}\n - **成绩**\t{}\n - **学分**\t{}\n - **绩点**\t{}\n - **成绩变化**\t{:.2}({:+.2}) / {:.1}({:+.1})",
-            xkkh, kcmc, cj, xf, jd, new_gpa, new_gpa - old_gpa, total_credit, total_credit - old_total_credit
-        );
-        info!("notify_score - ding md text: {}", markdown_text);
-        let json = json!({
-            "msgtype": "markdown",
-            "markdown": {
-                "title": "考试成绩通知",
-                "text": markdown_text
-            }
-        });
-        let client = reqwest::Client::new();
-        let res = client
-            .post(&ding_url)
-            .json(&json)
-            .send()
-            .await
-            .map_err(|err| err.to_string())?;
-        info!("notify_score - ding res: {:?}", res);
-    }
-
-    // notify_rust::Notification::new()
-    //     .summary(&format!("考试成绩通知 - {}", kcmc))
-    //     .body(&format!(
-    //         "成绩: {}\n学分: {}\n绩点: {}\n成绩变化: {:.2}({:+.2}) / {:.1}({:+.1})",
-    //         cj,
-    //         xf,
-    //         jd,
-    //         new_gpa,
-    //         new_gpa - old_gpa,
-    //         total_credit,
-    //         total_credit - old_total_credit
-    //     ))
-    //     .show()
-    //     .map_err(|err| err.to_string())?;
+#[tauri::command]
+pub async fn test_notify_channel(channel: NotifyChannel) -> Result<(), String> {
+    info!("test_notify_channel: {} {}", channel.kind, channel.name);
+    send_notification(
+        &channel,
+        "测试消息",
+        &format!(
+            "这是一条来自 ZJU Learning Assistant 的测试消息，渠道：{}",
+            channel.name
+        ),
+    )
+    .await
+}
 
-    handle
-        .notification()
-        .builder()
-        .title(&format!("考试成绩通知 - {}", kcmc))
-        .body(&format!(
-            "成绩: {}\n学分: {}\n绩点: {}\n成绩变化: {:.2}({:+.2}) / {:.1}({:+.1})",
-            cj,
-            xf,
-            jd,
-            new_gpa,
-            new_gpa - old_gpa,
-            total_credit,
-            total_credit - old_total_credit
-        ))
-        .show()
-        .unwrap();
+#[tauri::command]
+pub async fn test_email(config: State<'_, Arc<Mutex<Config>>>) -> Result<(), String> {
+    info!("test_email");
+    let config = config.lock().await.clone();
+    send_email(
+        &config,
+        "测试邮件",
+        "这是一封来自 ZJU Learning Assistant 的测试邮件。",
+    )
+    .await
+}
 
-    Ok(())
+// send the digest right away, whatever the schedule says
+#[tauri::command]
+pub async fn send_digest_now(
+    handle: AppHandle,
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
+) -> Result<(), String> {
+    info!("send_digest_now");
+    let zju_assist = state.lock().await.clone();
+    let config = config.lock().await.clone();
+    send_digest(&handle, &config, &zju_assist).await
 }
 
 #[tauri::command]
@@ -2104,7 +2937,7 @@ pub async fn get_config(config: State<'_, Arc<Mutex<Config>>>) -> Result<Config,
 pub async fn set_config(
     handle: AppHandle,
     config_state: State<'_, Arc<Mutex<Config>>>,
-    config: Config,
+    mut config: Config,
 ) -> Result<(), String> {
     info!("set_config");
     // 检查 llm_temperature 范围
@@ -2112,6 +2945,14 @@ pub async fn set_config(
         return Err("LLM 温度值必须在 0.0 到 2.0 之间".to_string());
     }
     let mut current_config = config_state.lock().await;
+    // the calendar feed always needs a token, keep the current one if any
+    if config.calendar_feed_token.is_empty() {
+        config.calendar_feed_token = if current_config.calendar_feed_token.is_empty() {
+            generate_token()
+        } else {
+            current_config.calendar_feed_token.clone()
+        };
+    }
     let origin_auto_start = current_config.auto_start;
     let new_auto_start = config.auto_start;
     if origin_auto_start != new_auto_start {
@@ -2123,9 +2964,15 @@ pub async fn set_config(
             autostart_manager.disable().map_err(|err| err.to_string())?;
         }
     }
+    let feed_changed = current_config.calendar_feed_enabled != config.calendar_feed_enabled
+        || current_config.calendar_feed_port != config.calendar_feed_port;
     *current_config = config.clone();
     drop(current_config);
 
+    if feed_changed {
+        restart_calendar_feed(handle.clone()).await;
+    }
+
     // save config to file
     if let Ok(config_path) = handle.path().app_config_dir() {
         // if config path not exists, create it
@@ -2140,6 +2987,239 @@ pub async fn set_config(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_calendar_feed_url(
+    config: State<'_, Arc<Mutex<Config>>>,
+    name: String,
+) -> Result<String, String> {
+    let config = config.lock().await.clone();
+    if !config.calendar_feed_enabled {
+        return Err("日历订阅未开启".to_string());
+    }
+    Ok(feed_url(&config, &name))
+}
+
+// export without a dialog, format is one of ics, md, json, org and txt
+#[tauri::command]
+pub async fn export_todo_file(
+    handle: AppHandle,
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
+    format: String,
+    path: String,
+) -> Result<(), String> {
+    info!("export_todo_file: {} to {}", format, path);
+    let zju_assist = state.lock().await.clone();
+    let todo_list = zju_assist
+        .get_todo_list()
+        .await
+        .map_err(|err| err.to_string())?;
+    if format == "ics" {
+        let alarms = config.lock().await.ics_alarm_minutes.clone();
+        let sequences = load_ics_sequences(&handle, &todo_list)?;
+        return export_todo_ics(todo_list, &path, &alarms, &sequences)
+            .map_err(|err| err.to_string());
+    }
+    let exporter = todo_exporter(&format).ok_or(format!("不支持的格式：{}", format))?;
+    std::fs::write(&path, exporter.export(&todo_list)).map_err(|err| err.to_string())
+}
+
+// let the user pick where to save a generated file, the result is notified
+fn save_file_with_dialog(
+    handle: AppHandle,
+    window: &Window,
+    filter: (&str, &str),
+    file_name: &str,
+    subject: &'static str,
+    content: Vec<u8>,
+) {
+    #[cfg(desktop)]
+    window.set_focus().unwrap();
+    #[cfg(desktop)]
+    handle
+        .dialog()
+        .file()
+        .add_filter(filter.0, &[filter.1])
+        .set_file_name(file_name)
+        .set_parent(window)
+        .save_file(move |file_path| {
+            let file_path = match file_path {
+                Some(file_path) => file_path,
+                None => return,
+            };
+            let res = std::fs::write(file_path.to_string(), content).map_err(|err| err.to_string());
+            match res {
+                Ok(_) => {
+                    handle
+                        .notification()
+                        .builder()
+                        .title(&format!("导出{}成功", subject))
+                        .body(&format!("文件已保存至：{}", file_path.to_string()))
+                        .show()
+                        .unwrap();
+                }
+                Err(err) => {
+                    handle
+                        .notification()
+                        .builder()
+                        .title(&format!("导出{}失败", subject))
+                        .body(&err)
+                        .show()
+                        .unwrap();
+                }
+            }
+        });
+}
+
+// sessions of the zdbk timetable when year and term are given, otherwise the
+// classroom schedules of courses on Learning in ZJU
+async fn load_class_sessions(
+    zju_assist: &Arc<Mutex<ZjuAssist>>,
+    config: &Config,
+    year: Option<String>,
+    term: Option<String>,
+) -> Result<Vec<ClassSession>, String> {
+    let mut sessions = match (year, term) {
+        (Some(year), Some(term)) => {
+            let mut zju_assist = zju_assist.lock().await.clone();
+            zju_assist
+                .get_zdbk_timetable(&year, &term)
+                .await
+                .map_err(|err| err.to_string())?
+        }
+        _ => {
+            let zju_assist = zju_assist.lock().await.clone();
+            let courses = zju_assist
+                .get_courses()
+                .await
+                .map_err(|err| err.to_string())?;
+            courses.iter().flat_map(parse_classroom_schedule).collect()
+        }
+    };
+    normalize_sessions(&mut sessions, &config.campus_period_times);
+    Ok(sessions)
+}
+
+#[tauri::command]
+pub async fn get_class_sessions(
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
+) -> Result<Vec<ClassSession>, String> {
+    info!("get_class_sessions");
+    let config = config.lock().await.clone();
+    load_class_sessions(&state, &config, None, None).await
+}
+
+// year like "2024-2025" and term like "1|秋"
+#[tauri::command]
+pub async fn get_zdbk_timetable(
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
+    year: String,
+    term: String,
+) -> Result<Vec<ClassSession>, String> {
+    info!("get_zdbk_timetable: {} {}", year, term);
+    let config = config.lock().await.clone();
+    load_class_sessions(&state, &config, Some(year), Some(term)).await
+}
+
+// semester_start (%Y-%m-%d) is the first day of week 1, required for the zdbk
+// timetable and used for courses without a start date otherwise
+#[tauri::command]
+pub async fn export_timetable_ics(
+    handle: AppHandle,
+    window: Window,
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
+    year: Option<String>,
+    term: Option<String>,
+    semester_start: Option<String>,
+) -> Result<(), String> {
+    info!("export_timetable_ics");
+    let semester_start = semester_start
+        .map(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d"))
+        .transpose()
+        .map_err(|err| err.to_string())?;
+    let config = config.lock().await.clone();
+    let sessions = load_class_sessions(&state, &config, year, term).await?;
+    if sessions.is_empty() {
+        return Err("没有找到课程时间安排".to_string());
+    }
+    let content = timetable_to_ics(&sessions, semester_start);
+
+    save_file_with_dialog(
+        handle,
+        &window,
+        ("iCalendar", "ics"),
+        "Timetable",
+        "课表",
+        content.into_bytes(),
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn sync_caldav_timetable(
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
+    year: Option<String>,
+    term: Option<String>,
+    semester_start: Option<String>,
+) -> Result<usize, String> {
+    info!("sync_caldav_timetable");
+    let semester_start = semester_start
+        .map(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d"))
+        .transpose()
+        .map_err(|err| err.to_string())?;
+    let config = config.lock().await.clone();
+    if config.caldav_url.is_empty() {
+        return Err("未配置 CalDAV 地址".to_string());
+    }
+    let client = CalDavClient::new(
+        &config.caldav_url,
+        &config.caldav_username,
+        &config.caldav_password,
+    );
+    let sessions = load_class_sessions(&state, &config, year, term).await?;
+    let mut count = 0;
+    for session in sessions.iter() {
+        if let Some(ics) = session_to_ics(session, semester_start) {
+            client.put(&session_uid(session), ics).await?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+#[tauri::command]
+pub async fn test_caldav_connection(
+    url: String,
+    username: String,
+    password: String,
+) -> Result<(), String> {
+    info!("test_caldav_connection: {}", url);
+    CalDavClient::new(&url, &username, &password).check().await
+}
+
+#[tauri::command]
+pub async fn sync_caldav(
+    handle: AppHandle,
+    state: State<'_, Arc<Mutex<ZjuAssist>>>,
+    config: State<'_, Arc<Mutex<Config>>>,
+) -> Result<usize, String> {
+    info!("sync_caldav");
+    let config = config.lock().await.clone();
+    if config.caldav_url.is_empty() {
+        return Err("未配置 CalDAV 地址".to_string());
+    }
+    let zju_assist = state.lock().await.clone();
+    let todo_list = zju_assist
+        .get_todo_list()
+        .await
+        .map_err(|err| err.to_string())?;
+    sync_caldav_todos(&handle, &config, &todo_list).await
+}
+
 async fn summarize_subtitle(
     contents: &Vec<SubtitleContent>,
     base_path: &Path,
@@ -1,5 +1,7 @@
+mod calendar_feed;
 mod controller;
 mod model;
+mod scheduler;
 mod utils;
 mod zju_assist;
 
@@ -88,6 +90,7 @@ pub fn run() {
                 to_pdf: true,
                 auto_download: true,
                 ding_url: "".to_string(),
+                ding_secret: "".to_string(),
                 auto_open_download_list: true,
                 tray: true,
                 max_concurrent_tasks: 3,
@@ -105,6 +108,51 @@ pub fn run() {
                 llm_temperature: 0.2,
                 llm_prompt: "你是一个专业的课程助教。请根据提供的课程字幕内容，总结课程的核心知识点、重点和难点。输出格式要求清晰、结构化，使用 Markdown 格式。".to_string(), 
                 llm_hide_think_tag: true,
+
+                todo_poll_interval: 30,
+                todo_reminder_offsets: vec![3 * 24 * 60, 24 * 60, 2 * 60],
+                todo_change_notify: true,
+                ics_alarm_minutes: vec![60],
+
+                calendar_feed_enabled: false,
+                calendar_feed_port: 18765,
+                calendar_feed_token: utils::generate_token(),
+
+                caldav_enabled: false,
+                caldav_url: "".to_string(),
+                caldav_username: "".to_string(),
+                caldav_password: "".to_string(),
+
+                exam_poll_interval: 180,
+                exam_notify: true,
+
+                notify_channels: vec![],
+
+                smtp_enabled: false,
+                smtp_host: "".to_string(),
+                smtp_port: 465,
+                smtp_security: "tls".to_string(),
+                smtp_username: "".to_string(),
+                smtp_password: "".to_string(),
+                smtp_from: "".to_string(),
+                smtp_to: "".to_string(),
+                smtp_events: vec![model::NotifyEvent::Score],
+                digest_frequency: "off".to_string(),
+                digest_hour: 8,
+
+                score_watch: false,
+                score_poll_interval: 5,
+
+                seat_watch: false,
+                seat_watch_xkkhs: vec![],
+                seat_poll_interval: 60,
+
+                evaluation_poll_interval: 360,
+                evaluation_remind_days: vec![3, 1],
+
+                announcement_poll_interval: 60,
+
+                campus_period_times: std::collections::HashMap::new(),
             };
 
             #[cfg(desktop)]
@@ -133,6 +181,11 @@ pub fn run() {
 
             let zju_assist = Arc::new(Mutex::new(ZjuAssist::new()));
 
+            // the pdf exports embed a cjk font bundled under resources/fonts if present
+            if let Ok(resource_dir) = app.path().resource_dir() {
+                utils::set_font_dir(resource_dir.join("fonts"));
+            }
+
             // get user download path
             if let Ok(download_dir) = app.path().download_dir() {
                 config.save_path = download_dir.to_str().unwrap().to_string();
@@ -145,6 +198,15 @@ pub fn run() {
                         config = config_local;
                     }
                 }
+                // the calendar feed never runs without a token, save the new one so
+                // existing subscriptions keep working after a restart
+                if config.calendar_feed_token.is_empty() {
+                    config.calendar_feed_token = utils::generate_token();
+                    if let Ok(config_str) = serde_json::to_string_pretty(&config) {
+                        let _ = std::fs::create_dir_all(&config_dir);
+                        let _ = std::fs::write(config_dir.join("config.json"), config_str);
+                    }
+                }
             }
             let config_state = Arc::new(Mutex::new(config));
 
@@ -154,6 +216,17 @@ pub fn run() {
             app.manage(config_state);
             app.manage(download_states);
 
+            scheduler::spawn_todo_poller(app.handle().clone());
+            scheduler::spawn_exam_poller(app.handle().clone());
+            scheduler::spawn_score_poller(app.handle().clone());
+            scheduler::spawn_digest_poller(app.handle().clone());
+            scheduler::spawn_evaluation_poller(app.handle().clone());
+            scheduler::spawn_seat_poller(app.handle().clone());
+            scheduler::spawn_announcement_poller(app.handle().clone());
+            tauri::async_runtime::spawn(calendar_feed::restart_calendar_feed(
+                app.handle().clone(),
+            ));
+
             let version = app.config().version.clone();
             info!("Current version: {:?}", version);
             let platform = tauri_plugin_os::platform();
@@ -207,11 +280,13 @@ pub fn run() {
                 if id == "quit" {
                     app.exit(0);
                 } else if id == "open" {
+                    scheduler::clear_todo_badge(app);
                     app.get_webview_window("main").unwrap().show().unwrap();
                     app.get_webview_window("main").unwrap().set_focus().unwrap();
                 } else if id.starts_with("export-todo-") {
                     app.emit("export-todo", id[12..].to_string()).unwrap();
                 } else if id.starts_with("todo-") {
+                    scheduler::clear_todo_badge(app);
                     let course_id_id = id.split("-").collect::<Vec<&str>>();
                     let course_id = course_id_id[1];
                     let id = course_id_id[2];
@@ -266,13 +341,30 @@ pub fn run() {
             controller::logout,
             controller::sync_todo_once,
             controller::export_todo,
+            controller::export_todo_file,
             controller::get_courses,
             controller::get_academic_year_list,
             controller::get_semester_list,
             controller::get_activities_uploads,
             controller::get_homework_uploads,
+            controller::get_homework_dashboard,
+            controller::get_homework_feedback_uploads,
+            controller::get_quiz_activities,
+            controller::export_quizzes,
+            controller::get_course_topics,
+            controller::export_course_topics,
+            controller::get_calendar_feed_url,
+            controller::test_caldav_connection,
+            controller::sync_caldav,
+            controller::get_class_sessions,
+            controller::get_zdbk_timetable,
+            controller::export_timetable_ics,
+            controller::sync_caldav_timetable,
+            controller::get_exams,
+            controller::export_exams_ics,
             controller::download_file,
             controller::get_uploads_list,
+            controller::get_submission_uploads_list,
             controller::start_download_upload,
             controller::cancel_download,
             controller::open_file,
@@ -281,14 +373,29 @@ pub fn run() {
             controller::get_latest_version_info,
             controller::start_download_ppts,
             controller::start_download_playback,
+            controller::get_video_activities_list,
+            controller::start_download_video,
             controller::get_range_subs,
             controller::search_courses,
             controller::get_course_all_sub_ppts,
             controller::get_sub_ppt_urls,
             controller::get_month_subs,
             controller::check_evaluation_done,
+            controller::get_evaluation_status,
             controller::get_score,
+            controller::sync_score,
+            controller::get_score_snapshot,
+            controller::export_score,
+            controller::get_gpa_breakdown,
+            controller::get_credit_audit,
+            controller::get_score_analytics,
+            controller::export_score_analytics,
+            controller::get_seat_snapshot,
+            controller::check_seats_now,
             controller::notify_score,
+            controller::test_notify_channel,
+            controller::test_email,
+            controller::send_digest_now,
             controller::get_config,
             controller::set_config,
             controller::test_llm_connection,
@@ -1,4 +1,7 @@
+use crate::utils::generate_token;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -6,6 +9,8 @@ pub struct Config {
     pub to_pdf: bool,
     pub auto_download: bool,
     pub ding_url: String,
+    #[serde(default)]
+    pub ding_secret: String, // signing secret of the robot, empty for the keyword mode
     pub auto_open_download_list: bool,
     pub tray: bool,
     pub max_concurrent_tasks: u32,
@@ -23,6 +28,177 @@ pub struct Config {
     pub llm_temperature: f64,
     pub llm_prompt: String,
     pub llm_hide_think_tag: bool,
+
+    #[serde(default = "default_todo_poll_interval")]
+    pub todo_poll_interval: u64, // minutes
+    #[serde(default = "default_todo_reminder_offsets")]
+    pub todo_reminder_offsets: Vec<u64>, // minutes before end_time, empty to disable
+    #[serde(default = "default_true")]
+    pub todo_change_notify: bool,
+    #[serde(default = "default_ics_alarm_minutes")]
+    pub ics_alarm_minutes: Vec<u64>, // alarms of exported ics events, minutes before end_time
+
+    #[serde(default)]
+    pub calendar_feed_enabled: bool,
+    #[serde(default = "default_calendar_feed_port")]
+    pub calendar_feed_port: u16,
+    #[serde(default = "generate_token")]
+    pub calendar_feed_token: String,
+
+    #[serde(default)]
+    pub caldav_enabled: bool,
+    #[serde(default)]
+    pub caldav_url: String, // calendar collection url
+    #[serde(default)]
+    pub caldav_username: String,
+    #[serde(default)]
+    pub caldav_password: String,
+
+    #[serde(default = "default_exam_poll_interval")]
+    pub exam_poll_interval: u64, // minutes, 0 to disable
+    #[serde(default = "default_true")]
+    pub exam_notify: bool,
+
+    #[serde(default)]
+    pub notify_channels: Vec<NotifyChannel>, // besides ding_url
+
+    #[serde(default)]
+    pub smtp_enabled: bool,
+    #[serde(default)]
+    pub smtp_host: String,
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+    #[serde(default = "default_smtp_security")]
+    pub smtp_security: String, // tls, starttls or none
+    #[serde(default)]
+    pub smtp_username: String,
+    #[serde(default)]
+    pub smtp_password: String,
+    #[serde(default)]
+    pub smtp_from: String, // empty for smtp_username
+    #[serde(default)]
+    pub smtp_to: String, // comma separated, empty for the sender
+    #[serde(default = "default_smtp_events")]
+    pub smtp_events: Vec<NotifyEvent>, // sent right away
+    #[serde(default = "default_digest_frequency")]
+    pub digest_frequency: String, // off, daily or weekly
+    #[serde(default = "default_digest_hour")]
+    pub digest_hour: u32, // local hour to send the digest
+
+    #[serde(default)]
+    pub score_watch: bool, // poll scores in the background and notify on changes
+    #[serde(default = "default_score_poll_interval")]
+    pub score_poll_interval: u64, // minutes
+
+    #[serde(default)]
+    pub seat_watch: bool,
+    #[serde(default)]
+    pub seat_watch_xkkhs: Vec<String>, // teaching classes to watch during course selection
+    #[serde(default = "default_seat_poll_interval")]
+    pub seat_poll_interval: u64, // seconds, never below 30
+
+    #[serde(default = "default_evaluation_poll_interval")]
+    pub evaluation_poll_interval: u64, // minutes, 0 to disable
+    #[serde(default = "default_evaluation_remind_days")]
+    pub evaluation_remind_days: Vec<u64>, // days before the window closes, empty to disable
+
+    #[serde(default = "default_announcement_poll_interval")]
+    pub announcement_poll_interval: u64, // minutes, 0 to disable
+
+    #[serde(default)]
+    pub campus_period_times: HashMap<String, Vec<String>>, // campus -> ["08:00-08:45", ...]
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_todo_poll_interval() -> u64 {
+    30
+}
+
+fn default_todo_reminder_offsets() -> Vec<u64> {
+    vec![3 * 24 * 60, 24 * 60, 2 * 60]
+}
+
+fn default_ics_alarm_minutes() -> Vec<u64> {
+    vec![60]
+}
+
+fn default_exam_poll_interval() -> u64 {
+    180
+}
+
+fn default_score_poll_interval() -> u64 {
+    5
+}
+
+fn default_seat_poll_interval() -> u64 {
+    60
+}
+
+fn default_evaluation_poll_interval() -> u64 {
+    360
+}
+
+fn default_announcement_poll_interval() -> u64 {
+    60
+}
+
+fn default_evaluation_remind_days() -> Vec<u64> {
+    vec![3, 1]
+}
+
+fn default_smtp_port() -> u16 {
+    465
+}
+
+fn default_smtp_security() -> String {
+    "tls".to_string()
+}
+
+fn default_smtp_events() -> Vec<NotifyEvent> {
+    vec![NotifyEvent::Score]
+}
+
+fn default_digest_frequency() -> String {
+    "off".to_string()
+}
+
+fn default_digest_hour() -> u32 {
+    8
+}
+
+fn default_calendar_feed_port() -> u16 {
+    18765
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyEvent {
+    Score,
+    Todo,
+    Exam,
+    Download,
+    Announcement,
+    Evaluation,
+    Seat,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NotifyChannel {
+    pub name: String,
+    pub kind: String, // ding, feishu, wecom, telegram, bark, serverchan, ntfy or gotify
+    #[serde(default)]
+    pub url: String, // webhook or server url
+    #[serde(default)]
+    pub token: String, // signing secret, bot token, device key, sendkey or app token
+    #[serde(default)]
+    pub target: String, // telegram chat id or ntfy topic
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub events: Vec<NotifyEvent>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -57,6 +233,327 @@ pub struct Subject {
     pub ppt_image_urls: Vec<String>,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoChangeKind {
+    New,
+    Removed,
+    DeadlineChanged,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TodoChange {
+    pub kind: TodoChangeKind,
+    pub todo: Value,
+    pub old_end_time: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct VideoActivity {
+    pub id: i64,
+    pub course_id: i64,
+    pub course_name: String,
+    pub file_name: String,
+    pub url: String,
+    pub path: String, // actual save path is path + file_name
+    pub size: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HomeworkStatus {
+    pub id: i64,
+    pub course_id: i64,
+    pub title: String,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub submitted: bool,
+    pub submitted_at: Option<String>,
+    pub is_late: bool,
+    pub score: Option<String>,
+    pub comment: String,
+    pub submission_uploads: Vec<Value>,
+    pub feedback_uploads: Vec<Value>, // files returned / annotated by the teacher
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CourseHomework {
+    pub course_id: i64,
+    pub course_name: String,
+    pub homeworks: Vec<HomeworkStatus>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct QuizQuestion {
+    pub id: i64,
+    pub description: String,  // html
+    pub options: Vec<String>, // html, labeled A, B, C... in order
+    pub answer: String,
+    pub correct_answer: Option<String>, // only available after the quiz is closed
+    pub point: Option<f64>,
+    pub score: Option<f64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct QuizAttempt {
+    pub id: i64,
+    pub score: Option<f64>,
+    pub submitted_at: Option<String>,
+    pub questions: Vec<QuizQuestion>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct QuizActivity {
+    pub id: i64,
+    pub course_id: i64,
+    pub title: String,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub total_points: Option<f64>,
+    pub is_closed: bool,
+    pub attempts: Vec<QuizAttempt>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TopicReply {
+    pub id: i64,
+    pub author: String,
+    pub content: String, // html
+    pub created_at: String,
+    pub uploads: Vec<Value>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CourseAnnouncement {
+    pub id: i64,
+    pub course_id: i64,
+    pub course_name: String,
+    pub title: String,
+    pub content: String, // html
+    pub created_at: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Topic {
+    pub id: i64,
+    pub course_id: i64,
+    pub title: String,
+    pub author: String,
+    pub content: String, // html
+    pub created_at: String,
+    pub updated_at: String,
+    pub uploads: Vec<Value>,
+    pub replies: Vec<TopicReply>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ClassSession {
+    pub course_id: String, // id on Learning in ZJU, or course code from ZDBK
+    pub course_name: String,
+    pub weekday: u32, // 1 = Monday
+    pub start_period: u32,
+    pub end_period: u32,
+    pub weeks: Vec<u32>, // teaching weeks, 1-based
+    pub location: String,
+    pub campus: Option<String>,
+    pub start_time: Option<String>, // %H:%M of start_period
+    pub end_time: Option<String>,   // %H:%M of end_period
+    pub start_date: Option<String>, // first day of week 1, %Y-%m-%d
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExamKind {
+    Midterm,
+    Final,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exam {
+    pub id: String,          // course_code-kind
+    pub course_code: String, // xkkh
+    pub course_name: String,
+    pub credit: Option<f64>,
+    pub semester: String,
+    pub kind: ExamKind,
+    pub time: Option<String>,       // as shown on zdbk, None until published
+    pub start_time: Option<String>, // rfc3339
+    pub end_time: Option<String>,
+    pub location: Option<String>,
+    pub seat: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExamChange {
+    pub exam: Exam,
+    pub old_exam: Option<Exam>, // None for a new exam
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeatInfo {
+    pub xkkh: String,
+    pub course_name: String,
+    pub teacher: String,
+    pub capacity: i64,
+    pub enrolled: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SeatChange {
+    pub seat: SeatInfo,
+    pub old_seat: SeatInfo,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EvaluationCourse {
+    pub id: String, // jxb_id
+    pub course_name: String,
+    pub teacher: String,
+    pub done: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EvaluationStatus {
+    pub done: bool,                     // as index_cxMyCosJxpj reports
+    pub courses: Vec<EvaluationCourse>, // of the current round
+    pub start_time: Option<String>,     // rfc3339, None if not announced
+    pub end_time: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScoreRecord {
+    pub xkkh: String,
+    pub course_code: String, // shared by retakes of the same course
+    pub semester: String,    // like 2023-2024-1
+    pub course_name: String,
+    pub score: String,                // cj
+    pub makeup_score: Option<String>, // bkcj
+    pub credit: f64,
+    pub grade_point: f64,           // jd as given by zdbk
+    pub effective_grade_point: f64, // after makeup rules
+    pub makeup_replaced: bool,
+    pub counted: bool, // false for non-graded records and superseded retakes
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct GpaOptions {
+    #[serde(default)]
+    pub major_course_codes: Option<Vec<String>>, // None for all courses
+    #[serde(default)]
+    pub excluded_course_codes: Vec<String>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct GpaSummary {
+    pub course_count: usize,
+    pub credit: f64,
+    pub gpa: f64,           // zju 5.0 scale
+    pub gpa_4_3: f64,       // zju 4.3 scale
+    pub gpa_4_0: f64,       // standard 4.0 scale
+    pub average_score: f64, // credit weighted percentage
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SemesterGpa {
+    pub semester: String,
+    pub summary: GpaSummary,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GpaBreakdown {
+    pub cumulative: GpaSummary,
+    pub semesters: Vec<SemesterGpa>, // in semester order
+    pub skipped: Vec<ScoreRecord>,   // records left out of every figure
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SemesterTrend {
+    pub semester: String,
+    pub gpa: f64, // of the semester alone
+    pub average_score: f64,
+    pub cumulative_gpa: f64, // up to and including the semester
+    pub earned_credit: f64,  // passed courses, graded or not
+    pub cumulative_credit: f64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GradeBucket {
+    pub label: String, // like 85-89
+    pub course_count: usize,
+    pub credit: f64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CategoryStat {
+    pub category: String,
+    pub summary: GpaSummary,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScoreAnalytics {
+    pub synced_at: String, // of the score snapshot the figures come from
+    pub trend: Vec<SemesterTrend>,
+    pub distribution: Vec<GradeBucket>, // low to high
+    pub categories: Vec<CategoryStat>,  // plan categories from the last credit audit
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlanCategory {
+    pub name: String, // like 通识, 专业必修 or 个性修读
+    pub required_credit: f64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlanCourse {
+    pub course_code: String,
+    pub course_name: String,
+    pub credit: f64,
+    pub category: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TrainingPlan {
+    pub categories: Vec<PlanCategory>,
+    pub courses: Vec<PlanCourse>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CategoryAudit {
+    pub name: String,
+    pub required_credit: f64, // 0 for categories the plan sets no minimum for
+    pub completed_credit: f64,
+    pub remaining_credit: f64,
+    pub courses: Vec<ScoreRecord>, // passed, one per course code
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CreditAudit {
+    pub categories: Vec<CategoryAudit>, // in plan order
+    pub required_credit: f64,
+    pub completed_credit: f64,
+    pub unmatched: Vec<ScoreRecord>, // passed but in no category
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScoreSnapshot {
+    pub username: String,
+    pub synced_at: String,   // %Y-%m-%d %H:%M:%S
+    pub score: Vec<Value>,   // zdbk items after annotate_score_items
+    pub summary: GpaSummary, // cumulative
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScoreChange {
+    pub score: Value,
+    pub old_score: Option<Value>, // None for a new record
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DigestUpload {
+    pub course_name: String,
+    pub file_name: String,
+    pub found_at: String, // rfc3339
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct VersionInfo {
     pub version: String,
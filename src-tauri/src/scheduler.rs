@@ -0,0 +1,1469 @@
+use crate::calendar_feed::load_ics_sequences;
+use crate::model::{
+    Config, CourseAnnouncement, DigestUpload, EvaluationStatus, Exam, ExamChange, GpaOptions,
+    GpaSummary, NotifyChannel, NotifyEvent, ScoreChange, ScoreSnapshot, SeatChange, SeatInfo,
+    TodoChange, TodoChangeKind, Upload,
+};
+use crate::utils::{
+    annotate_score_items, build_digest, describe_seat, dispatch_notification, exam_kind_name,
+    format_offset, free_seats, gpa_breakdown, html_to_text, is_graded_score, load_json,
+    parse_score_records, plain_text, routed_channels, routed_email, save_json, send_email,
+    send_notification, todo_to_ics, todo_uid, upcoming_todos, CalDavClient,
+};
+use crate::zju_assist::ZjuAssist;
+
+use chrono::{DateTime, Datelike, Local, Utc};
+use log::info;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::Mutex;
+
+fn state_path(handle: &AppHandle, name: &str) -> Option<PathBuf> {
+    handle.path().app_data_dir().ok().map(|dir| dir.join(name))
+}
+
+async fn notify(handle: &AppHandle, config: &Config, event: NotifyEvent, title: &str, body: &str) {
+    if let Err(err) = handle
+        .notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .show()
+    {
+        info!("notify: desktop notification failed {}", err);
+    }
+    dispatch_notification(config, event, title, body).await;
+}
+
+// key of a fired reminder, a moved deadline gets reminded again
+fn reminder_key(todo: &Value, end_time: &str, offset: u64) -> String {
+    format!(
+        "{}-{}-{}-{}",
+        todo["course_id"].as_i64().unwrap_or(0),
+        todo["id"].as_i64().unwrap_or(0),
+        end_time,
+        offset
+    )
+}
+
+async fn check_todo_reminders(handle: &AppHandle, config: &Config, todo_list: &Vec<Value>) {
+    let path = match state_path(handle, "todo_reminders.json") {
+        Some(path) => path,
+        None => return,
+    };
+    let fired: HashSet<String> = load_json(&path).unwrap_or_default();
+    let mut new_fired = HashSet::new();
+    let now = Utc::now();
+
+    let mut offsets = config.todo_reminder_offsets.clone();
+    offsets.sort();
+    for todo in todo_list.iter() {
+        let end_time_str = match todo["end_time"].as_str() {
+            Some(end_time) => end_time,
+            None => continue,
+        };
+        let end_time = match end_time_str.parse::<DateTime<Utc>>() {
+            Ok(end_time) => end_time,
+            Err(_) => continue,
+        };
+        if end_time <= now {
+            continue;
+        }
+        // only the closest due offset is fired, larger ones that were missed
+        // (e.g. the app was not running) are marked as fired silently
+        let mut due = offsets
+            .iter()
+            .filter(|offset| end_time - chrono::Duration::minutes(**offset as i64) <= now);
+        if let Some(offset) = due.next() {
+            let key = reminder_key(todo, end_time_str, *offset);
+            if !fired.contains(&key) {
+                let title = todo["title"].as_str().unwrap_or("");
+                let course_name = todo["course_name"].as_str().unwrap_or("");
+                notify(
+                    handle,
+                    config,
+                    NotifyEvent::Todo,
+                    "待办事项即将截止",
+                    &format!(
+                        "{} 的 {} 将于 {} 截止（不足 {}）",
+                        course_name,
+                        title,
+                        end_time.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                        format_offset(*offset)
+                    ),
+                )
+                .await;
+            }
+            new_fired.insert(key);
+            for offset in due {
+                new_fired.insert(reminder_key(todo, end_time_str, *offset));
+            }
+        }
+    }
+
+    // finished or expired todos drop out of the state here
+    if let Err(err) = save_json(&path, &new_fired) {
+        info!("check_todo_reminders: save state failed {}", err);
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct TodoSnapshot {
+    todos: Vec<Value>,
+    unseen_new: usize, // shown as tray badge until the user opens the app
+}
+
+// serialize snapshot updates from the poller and sync_todo_once
+static TODO_SNAPSHOT_LOCK: Mutex<()> = Mutex::const_new(());
+
+fn todo_key(todo: &Value) -> String {
+    format!(
+        "{}-{}",
+        todo["course_id"].as_i64().unwrap_or(0),
+        todo["id"].as_i64().unwrap_or(0)
+    )
+}
+
+fn set_todo_badge(handle: &AppHandle, count: usize) {
+    #[cfg(desktop)]
+    if let Some(tray) = handle.tray_by_id("main") {
+        let title = if count > 0 {
+            Some(count.to_string())
+        } else {
+            None
+        };
+        let tooltip = if count > 0 {
+            format!("ZJU Learning Assistant - {} 个新待办事项", count)
+        } else {
+            "ZJU Learning Assistant".to_string()
+        };
+        let _ = tray.set_title(title.as_deref());
+        let _ = tray.set_tooltip(Some(&tooltip));
+    }
+}
+
+pub fn clear_todo_badge(handle: &AppHandle) {
+    let handle = handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let _lock = TODO_SNAPSHOT_LOCK.lock().await;
+        let path = match state_path(&handle, "todo_snapshot.json") {
+            Some(path) => path,
+            None => return,
+        };
+        let mut snapshot: TodoSnapshot = match load_json(&path) {
+            Some(snapshot) => snapshot,
+            None => return,
+        };
+        snapshot.unseen_new = 0;
+        if let Err(err) = save_json(&path, &snapshot) {
+            info!("clear_todo_badge: save state failed {}", err);
+        }
+        set_todo_badge(&handle, 0);
+    });
+}
+
+// todo list of the latest sync, used by the calendar feed
+pub async fn latest_todo_list(handle: &AppHandle) -> Option<Vec<Value>> {
+    let _lock = TODO_SNAPSHOT_LOCK.lock().await;
+    let snapshot: TodoSnapshot = load_json(&state_path(handle, "todo_snapshot.json")?)?;
+    Some(snapshot.todos)
+}
+
+fn diff_todo_list(old_list: &Vec<Value>, new_list: &Vec<Value>) -> Vec<TodoChange> {
+    let old_todos = old_list
+        .iter()
+        .map(|todo| (todo_key(todo), todo))
+        .collect::<HashMap<_, _>>();
+    let new_keys = new_list.iter().map(todo_key).collect::<HashSet<_>>();
+
+    let mut changes = Vec::new();
+    for todo in new_list.iter() {
+        match old_todos.get(&todo_key(todo)) {
+            None => changes.push(TodoChange {
+                kind: TodoChangeKind::New,
+                todo: todo.clone(),
+                old_end_time: None,
+            }),
+            Some(old_todo) if old_todo["end_time"] != todo["end_time"] => {
+                changes.push(TodoChange {
+                    kind: TodoChangeKind::DeadlineChanged,
+                    todo: todo.clone(),
+                    old_end_time: old_todo["end_time"].as_str().map(|s| s.to_string()),
+                })
+            }
+            _ => {}
+        }
+    }
+    for todo in old_list.iter() {
+        if !new_keys.contains(&todo_key(todo)) {
+            changes.push(TodoChange {
+                kind: TodoChangeKind::Removed,
+                todo: todo.clone(),
+                old_end_time: todo["end_time"].as_str().map(|s| s.to_string()),
+            });
+        }
+    }
+    changes
+}
+
+fn format_end_time(end_time: Option<&str>) -> String {
+    match end_time.and_then(|end_time| end_time.parse::<DateTime<Utc>>().ok()) {
+        Some(end_time) => end_time
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        None => "无截止时间".to_string(),
+    }
+}
+
+// diff the fetched todo list against the persisted one, emit `todo-changes`
+// and notify about new todos and moved deadlines
+pub async fn check_todo_changes(handle: &AppHandle, config: &Config, todo_list: &Vec<Value>) {
+    let _lock = TODO_SNAPSHOT_LOCK.lock().await;
+    let path = match state_path(handle, "todo_snapshot.json") {
+        Some(path) => path,
+        None => return,
+    };
+    let snapshot: Option<TodoSnapshot> = load_json(&path);
+    let mut snapshot = match snapshot {
+        Some(snapshot) => snapshot,
+        None => {
+            // first sync, nothing to compare with
+            let snapshot = TodoSnapshot {
+                todos: todo_list.clone(),
+                unseen_new: 0,
+            };
+            if let Err(err) = save_json(&path, &snapshot) {
+                info!("check_todo_changes: save state failed {}", err);
+            }
+            return;
+        }
+    };
+
+    let changes = diff_todo_list(&snapshot.todos, todo_list);
+    if !changes.is_empty() {
+        info!("check_todo_changes: {} changes", changes.len());
+        let _ = handle.emit("todo-changes", changes.clone());
+    }
+
+    let mut new_count = 0;
+    for change in changes.iter() {
+        let title = change.todo["title"].as_str().unwrap_or("");
+        let course_name = change.todo["course_name"].as_str().unwrap_or("");
+        match change.kind {
+            TodoChangeKind::New => {
+                new_count += 1;
+                if config.todo_change_notify {
+                    notify(
+                        handle,
+                        config,
+                        NotifyEvent::Todo,
+                        "新的待办事项",
+                        &format!(
+                            "{} 发布了 {}，截止时间：{}",
+                            course_name,
+                            title,
+                            format_end_time(change.todo["end_time"].as_str())
+                        ),
+                    )
+                    .await;
+                }
+            }
+            TodoChangeKind::DeadlineChanged => {
+                if config.todo_change_notify {
+                    notify(
+                        handle,
+                        config,
+                        NotifyEvent::Todo,
+                        "待办事项截止时间变更",
+                        &format!(
+                            "{} 的 {} 截止时间由 {} 变更为 {}",
+                            course_name,
+                            title,
+                            format_end_time(change.old_end_time.as_deref()),
+                            format_end_time(change.todo["end_time"].as_str())
+                        ),
+                    )
+                    .await;
+                }
+            }
+            TodoChangeKind::Removed => {}
+        }
+    }
+
+    snapshot.todos = todo_list.clone();
+    snapshot.unseen_new += new_count;
+    if new_count > 0 {
+        set_todo_badge(handle, snapshot.unseen_new);
+    }
+    if let Err(err) = save_json(&path, &snapshot) {
+        info!("check_todo_changes: save state failed {}", err);
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CalDavResource {
+    fingerprint: String,
+    end_time: Option<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CalDavState {
+    collection_url: String,
+    resources: HashMap<String, CalDavResource>, // uid -> pushed resource
+}
+
+static CALDAV_LOCK: Mutex<()> = Mutex::const_new(());
+
+// push todos to the configured CalDAV collection, only changed todos are
+// uploaded and todos that disappeared before their deadline are deleted
+pub async fn sync_caldav_todos(
+    handle: &AppHandle,
+    config: &Config,
+    todo_list: &Vec<Value>,
+) -> Result<usize, String> {
+    let _lock = CALDAV_LOCK.lock().await;
+    let path = state_path(handle, "caldav_state.json").ok_or("无法获取数据目录")?;
+    let mut state: CalDavState = load_json(&path).unwrap_or_default();
+    if state.collection_url != config.caldav_url {
+        // another collection, push everything again
+        state = CalDavState {
+            collection_url: config.caldav_url.clone(),
+            resources: HashMap::new(),
+        };
+    }
+
+    let client = CalDavClient::new(
+        &config.caldav_url,
+        &config.caldav_username,
+        &config.caldav_password,
+    );
+    let sequences = load_ics_sequences(handle, todo_list)?;
+    let now = Utc::now();
+    let mut changed = 0;
+    let mut last_err = None;
+
+    for todo in todo_list.iter() {
+        let uid = todo_uid(todo);
+        let sequence = sequences.get(&uid).map_or(0, |s| s.sequence);
+        let fingerprint = format!(
+            "{:x}",
+            md5::compute(format!(
+                "{}{:?}{}",
+                todo, config.ics_alarm_minutes, sequence
+            ))
+        );
+        if state
+            .resources
+            .get(&uid)
+            .is_some_and(|resource| resource.fingerprint == fingerprint)
+        {
+            continue;
+        }
+        match client
+            .put(&uid, todo_to_ics(todo, &config.ics_alarm_minutes, sequence))
+            .await
+        {
+            Ok(_) => {
+                changed += 1;
+                state.resources.insert(
+                    uid,
+                    CalDavResource {
+                        fingerprint,
+                        end_time: todo["end_time"].as_str().map(|s| s.to_string()),
+                    },
+                );
+            }
+            Err(err) => {
+                info!("sync_caldav_todos: {}", err);
+                last_err = Some(err);
+            }
+        }
+    }
+
+    let current = todo_list.iter().map(todo_uid).collect::<HashSet<_>>();
+    let removed = state
+        .resources
+        .keys()
+        .filter(|uid| !current.contains(*uid))
+        .cloned()
+        .collect::<Vec<_>>();
+    for uid in removed {
+        // expired todos stay in the calendar as history
+        let expired = state.resources[&uid]
+            .end_time
+            .as_ref()
+            .and_then(|end_time| end_time.parse::<DateTime<Utc>>().ok())
+            .is_some_and(|end_time| end_time <= now);
+        if !expired {
+            if let Err(err) = client.delete(&uid).await {
+                info!("sync_caldav_todos: {}", err);
+                last_err = Some(err);
+                continue;
+            }
+            changed += 1;
+        }
+        state.resources.remove(&uid);
+    }
+
+    save_json(&path, &state)?;
+    match last_err {
+        Some(err) => Err(err),
+        None => Ok(changed),
+    }
+}
+
+pub fn spawn_todo_poller(handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = handle.state::<Arc<Mutex<Config>>>().lock().await.clone();
+            let zju_assist = handle.state::<Arc<Mutex<ZjuAssist>>>().lock().await.clone();
+            if !zju_assist.is_login() {
+                // wait for the (auto) login
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                continue;
+            }
+            info!("todo_poller: sync");
+            match zju_assist.get_todo_list().await {
+                Ok(todo_list) => {
+                    check_todo_changes(&handle, &config, &todo_list).await;
+                    if config.caldav_enabled && !config.caldav_url.is_empty() {
+                        if let Err(err) = sync_caldav_todos(&handle, &config, &todo_list).await {
+                            info!("todo_poller: caldav sync failed {}", err);
+                        }
+                    }
+                    if !config.todo_reminder_offsets.is_empty() {
+                        check_todo_reminders(&handle, &config, &todo_list).await;
+                    }
+                }
+                Err(err) => info!("todo_poller: get_todo_list failed {}", err),
+            }
+            let interval = config.todo_poll_interval.max(1);
+            tokio::time::sleep(Duration::from_secs(interval * 60)).await;
+        }
+    });
+}
+
+static EXAM_SNAPSHOT_LOCK: Mutex<()> = Mutex::const_new(());
+
+// exams of the latest sync, used by the calendar feed
+pub async fn latest_exams(handle: &AppHandle) -> Option<Vec<Exam>> {
+    let _lock = EXAM_SNAPSHOT_LOCK.lock().await;
+    load_json(&state_path(handle, "exam_snapshot.json")?)
+}
+
+fn describe_exam(exam: &Exam) -> String {
+    format!(
+        "时间：{}，地点：{}，座位号：{}",
+        exam.time.as_deref().unwrap_or("未安排"),
+        exam.location.as_deref().unwrap_or("未安排"),
+        exam.seat.as_deref().unwrap_or("未安排")
+    )
+}
+
+// notify when an exam shows up or its time, room or seat is published or changed
+pub async fn check_exam_changes(handle: &AppHandle, config: &Config, exams: &Vec<Exam>) {
+    let _lock = EXAM_SNAPSHOT_LOCK.lock().await;
+    let path = match state_path(handle, "exam_snapshot.json") {
+        Some(path) => path,
+        None => return,
+    };
+    let old_exams: Option<Vec<Exam>> = load_json(&path);
+    if let Err(err) = save_json(&path, exams) {
+        info!("check_exam_changes: save state failed {}", err);
+    }
+    // first sync, nothing to compare with
+    let old_exams = match old_exams {
+        Some(old_exams) => old_exams
+            .into_iter()
+            .map(|exam| (exam.id.clone(), exam))
+            .collect::<HashMap<_, _>>(),
+        None => return,
+    };
+
+    let changes = exams
+        .iter()
+        .filter_map(|exam| match old_exams.get(&exam.id) {
+            None => Some(ExamChange {
+                exam: exam.clone(),
+                old_exam: None,
+            }),
+            Some(old_exam)
+                if old_exam.time != exam.time
+                    || old_exam.location != exam.location
+                    || old_exam.seat != exam.seat =>
+            {
+                Some(ExamChange {
+                    exam: exam.clone(),
+                    old_exam: Some(old_exam.clone()),
+                })
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if changes.is_empty() {
+        return;
+    }
+    info!("check_exam_changes: {} changes", changes.len());
+    let _ = handle.emit("exam-changes", changes.clone());
+
+    if !config.exam_notify {
+        return;
+    }
+    for change in changes.iter() {
+        let exam = &change.exam;
+        let title = match change.old_exam {
+            None => "新的考试安排",
+            Some(_) => "考试安排变更",
+        };
+        notify(
+            handle,
+            config,
+            NotifyEvent::Exam,
+            title,
+            &format!(
+                "{}（{}）{}",
+                exam.course_name,
+                exam_kind_name(&exam.kind),
+                describe_exam(exam)
+            ),
+        )
+        .await;
+    }
+}
+
+// remind once on the day before each exam
+async fn check_exam_reminders(handle: &AppHandle, config: &Config, exams: &Vec<Exam>) {
+    let path = match state_path(handle, "exam_reminders.json") {
+        Some(path) => path,
+        None => return,
+    };
+    let mut fired: HashSet<String> = load_json(&path).unwrap_or_default();
+    let now = Utc::now();
+
+    for exam in exams.iter() {
+        let start_time = match exam
+            .start_time
+            .as_ref()
+            .and_then(|time| time.parse::<DateTime<Utc>>().ok())
+        {
+            Some(start_time) => start_time,
+            None => continue,
+        };
+        if start_time <= now || start_time - chrono::Duration::days(1) > now {
+            continue;
+        }
+        // a rescheduled exam gets reminded again
+        let key = format!("{}-{}", exam.id, start_time.to_rfc3339());
+        if fired.insert(key) {
+            notify(
+                handle,
+                config,
+                NotifyEvent::Exam,
+                "明天有考试",
+                &format!(
+                    "{}（{}）{}",
+                    exam.course_name,
+                    exam_kind_name(&exam.kind),
+                    describe_exam(exam)
+                ),
+            )
+            .await;
+        }
+    }
+
+    if let Err(err) = save_json(&path, &fired) {
+        info!("check_exam_reminders: save state failed {}", err);
+    }
+}
+
+pub fn spawn_exam_poller(handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = handle.state::<Arc<Mutex<Config>>>().lock().await.clone();
+            let mut zju_assist = handle.state::<Arc<Mutex<ZjuAssist>>>().lock().await.clone();
+            if config.exam_poll_interval == 0 || !zju_assist.is_login() {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                continue;
+            }
+            info!("exam_poller: sync");
+            match zju_assist.get_exams("", "").await {
+                Ok(exams) => {
+                    check_exam_changes(&handle, &config, &exams).await;
+                    if config.exam_notify {
+                        check_exam_reminders(&handle, &config, &exams).await;
+                    }
+                }
+                Err(err) => info!("exam_poller: get_exams failed {}", err),
+            }
+            tokio::time::sleep(Duration::from_secs(config.exam_poll_interval * 60)).await;
+        }
+    });
+}
+
+static SCORE_SNAPSHOT_LOCK: Mutex<()> = Mutex::const_new(());
+// failed notification attempts per xkkh, retried on the following syncs
+static SCORE_NOTIFY_RETRIES: Mutex<BTreeMap<String, u32>> = Mutex::const_new(BTreeMap::new());
+const MAX_SCORE_NOTIFY_ATTEMPTS: u32 = 3;
+
+// zdbk rate limits frequent queries, never poll more often than this
+const MIN_SCORE_POLL_INTERVAL: u64 = 3;
+
+// score of the latest sync, so a reloaded window starts from it
+pub async fn latest_score(handle: &AppHandle) -> Option<ScoreSnapshot> {
+    let _lock = SCORE_SNAPSHOT_LOCK.lock().await;
+    load_json(&state_path(handle, "score_snapshot.json")?)
+}
+
+pub async fn notify_score_change(
+    handle: &AppHandle,
+    channels: &Vec<NotifyChannel>,
+    smtp: Option<&Config>, // mail with the smtp settings of this config
+    score: &Value,
+    old_summary: &GpaSummary,
+    summary: &GpaSummary,
+) -> Result<(), String> {
+    let xkkh = score["xkkh"].as_str().unwrap_or("");
+    let kcmc = score["kcmc"].as_str().unwrap_or("");
+    let xf = score["xf"].as_str().unwrap_or("");
+    let jd = score["jd"].as_str().unwrap_or("");
+    let bkcj = score["bkcj"].as_str().unwrap_or("").trim();
+    let cj = if score["makeup_replaced"].as_bool().unwrap_or(false) {
+        format!(
+            "{}（补考成绩，原成绩 {}）",
+            bkcj,
+            score["cj"].as_str().unwrap_or("")
+        )
+    } else if !bkcj.is_empty() {
+        format!(
+            "{}（补考成绩 {}）",
+            score["cj"].as_str().unwrap_or(""),
+            bkcj
+        )
+    } else {
+        score["cj"].as_str().unwrap_or("").to_string()
+    };
+    // a superseded retake does not change the gpa
+    let cj = if score["counted"].as_bool() == Some(false)
+        && is_graded_score(score["cj"].as_str().unwrap_or(""))
+    {
+        format!("{}（已有更高的重修成绩，不计入绩点）", cj)
+    } else {
+        cj
+    };
+    let change = format!(
+        "{:.2}({:+.2}) / {:.1}({:+.1})",
+        summary.gpa,
+        summary.gpa - old_summary.gpa,
+        summary.credit,
+        summary.credit - old_summary.credit
+    );
+
+    let markdown_text = format!(
+        " - **选课课号**\t{}\n - **课程名称**\t{}\n - **成绩**\t{}\n - **学分**\t{}\n - **绩点**\t{}\n - **成绩变化**\t{}",
+        xkkh, kcmc, cj, xf, jd, change
+    );
+    let mut last_err = None;
+    for channel in channels.iter() {
+        if let Err(err) = send_notification(channel, "考试成绩通知", &markdown_text).await {
+            info!("notify_score_change: {}", err);
+            last_err = Some(err);
+        }
+    }
+    if let Some(config) = smtp {
+        let subject = format!("考试成绩通知 - {}", kcmc);
+        if let Err(err) = send_email(config, &subject, &plain_text(&markdown_text)).await {
+            info!("notify_score_change: {}", err);
+            last_err = Some(err);
+        }
+    }
+
+    // only a failed remote send is worth a retry, it would repeat the message
+    // on every channel that already got it
+    if let Err(err) = handle
+        .notification()
+        .builder()
+        .title(format!("考试成绩通知 - {}", kcmc))
+        .body(format!(
+            "成绩: {}\n学分: {}\n绩点: {}\n成绩变化: {}",
+            cj, xf, jd, change
+        ))
+        .show()
+    {
+        info!("notify_score_change: desktop notification failed {}", err);
+    }
+
+    match last_err {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+fn is_score_changed(old_score: &Value, score: &Value) -> bool {
+    ["cj", "bkcj", "xf", "jd"]
+        .iter()
+        .any(|key| old_score[key] != score[key])
+}
+
+// save the synced score and notify about new or changed records, so a
+// reloaded window or a restart neither repeats nor misses a notification
+pub async fn check_score_changes(
+    handle: &AppHandle,
+    config: &Config,
+    username: &str,
+    mut score: Vec<Value>,
+) -> ScoreSnapshot {
+    annotate_score_items(&mut score);
+    let records = parse_score_records(&score);
+    let snapshot = ScoreSnapshot {
+        username: username.to_string(),
+        synced_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        summary: gpa_breakdown(&records, &GpaOptions::default()).cumulative,
+        score,
+    };
+
+    let _lock = SCORE_SNAPSHOT_LOCK.lock().await;
+    let path = match state_path(handle, "score_snapshot.json") {
+        Some(path) => path,
+        None => return snapshot,
+    };
+    let old_snapshot: Option<ScoreSnapshot> = load_json(&path);
+    let _ = handle.emit("score-updated", snapshot.clone());
+
+    // first sync of this account, nothing to compare with
+    let old_snapshot = match old_snapshot {
+        Some(old_snapshot) if old_snapshot.username == username => old_snapshot,
+        _ => {
+            save_score_snapshot(&path, &snapshot);
+            return snapshot;
+        }
+    };
+    let old_score = old_snapshot
+        .score
+        .iter()
+        .filter_map(|item| Some((item["xkkh"].as_str()?, item)))
+        .collect::<HashMap<_, _>>();
+    let changes = snapshot
+        .score
+        .iter()
+        .filter_map(|item| {
+            let old_item = old_score.get(item["xkkh"].as_str()?);
+            match old_item {
+                Some(old_item) if !is_score_changed(old_item, item) => None,
+                _ => Some(ScoreChange {
+                    score: item.clone(),
+                    old_score: old_item.map(|old_item| (*old_item).clone()),
+                }),
+            }
+        })
+        .collect::<Vec<_>>();
+    if changes.is_empty() {
+        save_score_snapshot(&path, &snapshot);
+        return snapshot;
+    }
+    info!("check_score_changes: {} changes", changes.len());
+    let _ = handle.emit("score-changes", changes.clone());
+
+    // records whose notification failed keep their old state in the saved
+    // snapshot, so the next sync finds them changed again and retries
+    let mut pending = HashSet::new();
+    if config.score_watch {
+        let mut retries = SCORE_NOTIFY_RETRIES.lock().await;
+        for change in changes.iter() {
+            let xkkh = change.score["xkkh"].as_str().unwrap_or("").to_string();
+            match notify_score_change(
+                handle,
+                &routed_channels(config, NotifyEvent::Score),
+                routed_email(config, NotifyEvent::Score).then_some(config),
+                &change.score,
+                &old_snapshot.summary,
+                &snapshot.summary,
+            )
+            .await
+            {
+                Ok(_) => {
+                    retries.remove(&xkkh);
+                }
+                Err(err) => {
+                    info!("check_score_changes: notify failed {}", err);
+                    let attempts = retries.entry(xkkh.clone()).or_insert(0);
+                    *attempts += 1;
+                    // give up on a channel that keeps failing
+                    if *attempts < MAX_SCORE_NOTIFY_ATTEMPTS {
+                        pending.insert(xkkh);
+                    } else {
+                        retries.remove(&xkkh);
+                    }
+                }
+            }
+        }
+    }
+
+    if pending.is_empty() {
+        save_score_snapshot(&path, &snapshot);
+    } else {
+        let score = snapshot
+            .score
+            .iter()
+            .filter_map(|item| match item["xkkh"].as_str() {
+                Some(xkkh) if pending.contains(xkkh) => {
+                    old_score.get(xkkh).map(|old_item| (*old_item).clone())
+                }
+                _ => Some(item.clone()),
+            })
+            .collect::<Vec<_>>();
+        // the items keep the annotations they were saved with
+        let records = parse_score_records(&score);
+        save_score_snapshot(
+            &path,
+            &ScoreSnapshot {
+                username: snapshot.username.clone(),
+                synced_at: snapshot.synced_at.clone(),
+                summary: gpa_breakdown(&records, &GpaOptions::default()).cumulative,
+                score,
+            },
+        );
+    }
+    snapshot
+}
+
+fn save_score_snapshot(path: &PathBuf, snapshot: &ScoreSnapshot) {
+    if let Err(err) = save_json(path, snapshot) {
+        info!("check_score_changes: save state failed {}", err);
+    }
+}
+
+pub fn spawn_score_poller(handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = handle.state::<Arc<Mutex<Config>>>().lock().await.clone();
+            let mut zju_assist = handle.state::<Arc<Mutex<ZjuAssist>>>().lock().await.clone();
+            if !config.score_watch || !zju_assist.is_login() {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                continue;
+            }
+            info!("score_poller: sync");
+            match zju_assist.get_score().await {
+                Ok(score) => {
+                    check_score_changes(&handle, &config, &zju_assist.get_username(), score).await;
+                }
+                Err(err) => info!("score_poller: get_score failed {}", err),
+            }
+            // a random delay keeps clients from hitting zdbk in lockstep
+            let interval = config.score_poll_interval.max(MIN_SCORE_POLL_INTERVAL) * 60;
+            let jitter = rand::thread_rng().gen_range(0..=interval / 5);
+            tokio::time::sleep(Duration::from_secs(interval + jitter)).await;
+        }
+    });
+}
+
+struct SyncDownloads {
+    active: usize,
+    files: Vec<String>, // course: file of each finished download
+}
+
+static SYNC_DOWNLOADS: Mutex<SyncDownloads> = Mutex::const_new(SyncDownloads {
+    active: 0,
+    files: Vec::new(),
+});
+// the next queued download starts as soon as one ends, wait a little before
+// treating the sync as finished
+const SYNC_DOWNLOAD_SETTLE: u64 = 10;
+// keep the message short enough for the webhooks
+const MAX_SYNC_DOWNLOAD_LINES: usize = 20;
+
+pub async fn begin_sync_download() {
+    SYNC_DOWNLOADS.lock().await.active += 1;
+}
+
+// called once for every begin_sync_download, file is None when the download
+// failed or was canceled. one message covers all files of a sync
+pub async fn finish_sync_download(config: &Config, file: Option<String>) {
+    {
+        let mut downloads = SYNC_DOWNLOADS.lock().await;
+        downloads.active = downloads.active.saturating_sub(1);
+        downloads.files.extend(file);
+        if downloads.active > 0 {
+            return;
+        }
+    }
+    tokio::time::sleep(Duration::from_secs(SYNC_DOWNLOAD_SETTLE)).await;
+    let files = {
+        let mut downloads = SYNC_DOWNLOADS.lock().await;
+        if downloads.active > 0 {
+            return;
+        }
+        std::mem::take(&mut downloads.files)
+    };
+    if files.is_empty() {
+        return;
+    }
+    let mut body = format!("已下载 {} 个新课件", files.len());
+    for file in files.iter().take(MAX_SYNC_DOWNLOAD_LINES) {
+        body.push_str(&format!("\n{}", file));
+    }
+    if files.len() > MAX_SYNC_DOWNLOAD_LINES {
+        body.push_str("\n……");
+    }
+    dispatch_notification(config, NotifyEvent::Download, "课件已下载", &body).await;
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct DigestState {
+    last_sent: Option<String>,              // rfc3339
+    uploads: HashMap<String, DigestUpload>, // reference_id -> first found by the upload sync
+}
+
+static DIGEST_LOCK: Mutex<()> = Mutex::const_new(());
+
+// remember when the upload sync first found each file
+pub async fn record_digest_uploads(handle: &AppHandle, uploads: &Vec<Upload>) {
+    let _lock = DIGEST_LOCK.lock().await;
+    let path = match state_path(handle, "digest_state.json") {
+        Some(path) => path,
+        None => return,
+    };
+    let mut state: DigestState = load_json(&path).unwrap_or_default();
+    let now = Utc::now().to_rfc3339();
+    for upload in uploads.iter() {
+        state
+            .uploads
+            .entry(upload.reference_id.to_string())
+            .or_insert_with(|| DigestUpload {
+                course_name: upload.course_name.clone(),
+                file_name: upload.file_name.clone(),
+                found_at: now.clone(),
+            });
+    }
+    if let Err(err) = save_json(&path, &state) {
+        info!("record_digest_uploads: save state failed {}", err);
+    }
+}
+
+fn digest_days(frequency: &str) -> i64 {
+    if frequency == "weekly" {
+        7
+    } else {
+        1
+    }
+}
+
+// the latest scheduled time not after now: today (daily) or this monday
+// (weekly) at digest_hour, a digest missed while the app was closed is sent late
+fn digest_due_time(config: &Config, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let mut date = now.date_naive();
+    if config.digest_frequency == "weekly" {
+        date -= chrono::Duration::days(now.weekday().num_days_from_monday() as i64);
+    }
+    let mut due = date
+        .and_hms_opt(config.digest_hour.min(23), 0, 0)?
+        .and_local_timezone(Local)
+        .earliest()?;
+    if due > now {
+        due -= chrono::Duration::days(digest_days(&config.digest_frequency));
+    }
+    Some(due)
+}
+
+pub async fn send_digest(
+    handle: &AppHandle,
+    config: &Config,
+    zju_assist: &ZjuAssist,
+) -> Result<(), String> {
+    let _lock = DIGEST_LOCK.lock().await;
+    let path = state_path(handle, "digest_state.json").ok_or("无法获取数据目录")?;
+    let mut state: DigestState = load_json(&path).unwrap_or_default();
+    let days = digest_days(&config.digest_frequency);
+    let now = Utc::now();
+    let since = state
+        .last_sent
+        .as_ref()
+        .and_then(|time| time.parse::<DateTime<Utc>>().ok())
+        .unwrap_or(now - chrono::Duration::days(days));
+
+    let todo_list = zju_assist
+        .get_todo_list()
+        .await
+        .map_err(|err| err.to_string())?;
+    let todos = upcoming_todos(&todo_list, now, now + chrono::Duration::days(days));
+    let mut uploads = state
+        .uploads
+        .values()
+        .filter(|upload| {
+            upload
+                .found_at
+                .parse::<DateTime<Utc>>()
+                .is_ok_and(|time| time > since)
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    uploads.sort_by(|a, b| a.found_at.cmp(&b.found_at));
+    // recordings of the lectures since the last digest
+    let subs = zju_assist
+        .get_range_subs(
+            &since.with_timezone(&Local).format("%Y-%m-%d").to_string(),
+            &Local::now().format("%Y-%m-%d").to_string(),
+        )
+        .await
+        .unwrap_or_else(|err| {
+            info!("send_digest: get_range_subs failed {}", err);
+            vec![]
+        });
+
+    let subject = format!(
+        "学在浙大{}摘要 - {}",
+        if days == 7 { "每周" } else { "每日" },
+        Local::now().format("%Y-%m-%d")
+    );
+    send_email(config, &subject, &build_digest(&todos, &uploads, &subs)).await?;
+
+    state.last_sent = Some(now.to_rfc3339());
+    // files older than a month will not make it into any digest
+    let cutoff = now - chrono::Duration::days(30);
+    state.uploads.retain(|_, upload| {
+        upload
+            .found_at
+            .parse::<DateTime<Utc>>()
+            .is_ok_and(|time| time > cutoff)
+    });
+    save_json(&path, &state)
+}
+
+pub fn spawn_digest_poller(handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(10 * 60)).await;
+            let config = handle.state::<Arc<Mutex<Config>>>().lock().await.clone();
+            let zju_assist = handle.state::<Arc<Mutex<ZjuAssist>>>().lock().await.clone();
+            if !config.smtp_enabled || config.digest_frequency == "off" || !zju_assist.is_login() {
+                continue;
+            }
+            let due = match digest_due_time(&config, Local::now()) {
+                Some(due) => due,
+                None => continue,
+            };
+            let last_sent = {
+                let _lock = DIGEST_LOCK.lock().await;
+                state_path(&handle, "digest_state.json")
+                    .and_then(|path| load_json::<DigestState>(&path))
+                    .and_then(|state| state.last_sent)
+                    .and_then(|time| time.parse::<DateTime<Local>>().ok())
+            };
+            if last_sent.is_some_and(|last_sent| last_sent >= due) {
+                continue;
+            }
+            info!("digest_poller: send");
+            if let Err(err) = send_digest(&handle, &config, &zju_assist).await {
+                info!("digest_poller: send failed {}", err);
+            }
+        }
+    });
+}
+
+static COURSE_CATEGORY_LOCK: Mutex<()> = Mutex::const_new(());
+
+// course code -> training plan category, kept from the last credit audit
+pub async fn save_course_categories(handle: &AppHandle, categories: &HashMap<String, String>) {
+    let _lock = COURSE_CATEGORY_LOCK.lock().await;
+    let path = match state_path(handle, "course_categories.json") {
+        Some(path) => path,
+        None => return,
+    };
+    if let Err(err) = save_json(&path, categories) {
+        info!("save_course_categories: save state failed {}", err);
+    }
+}
+
+pub async fn load_course_categories(handle: &AppHandle) -> HashMap<String, String> {
+    let _lock = COURSE_CATEGORY_LOCK.lock().await;
+    state_path(handle, "course_categories.json")
+        .and_then(|path| load_json(&path))
+        .unwrap_or_default()
+}
+
+// pending courses and the window are best effort, done alone decides
+pub async fn load_evaluation_status(
+    zju_assist: &mut ZjuAssist,
+) -> Result<EvaluationStatus, String> {
+    let done = zju_assist
+        .check_evaluation_done()
+        .await
+        .map_err(|err| err.to_string())?;
+    let courses = zju_assist
+        .get_evaluation_courses()
+        .await
+        .unwrap_or_else(|err| {
+            info!("load_evaluation_status: get courses failed {}", err);
+            Vec::new()
+        });
+    let window = zju_assist
+        .get_evaluation_window()
+        .await
+        .unwrap_or_else(|err| {
+            info!("load_evaluation_status: get window failed {}", err);
+            None
+        });
+    let (start_time, end_time) = window
+        .map(|(start, end)| (start.to_rfc3339(), end.to_rfc3339()))
+        .unzip();
+    Ok(EvaluationStatus {
+        done,
+        courses,
+        start_time,
+        end_time,
+    })
+}
+
+// remind once when each of the configured days before the window closes is
+// reached, thresholds already passed together are merged into one reminder
+async fn check_evaluation_reminders(
+    handle: &AppHandle,
+    config: &Config,
+    status: &EvaluationStatus,
+) {
+    if status.done {
+        return;
+    }
+    let end_time = match status
+        .end_time
+        .as_ref()
+        .and_then(|time| time.parse::<DateTime<Utc>>().ok())
+    {
+        Some(end_time) => end_time,
+        None => return,
+    };
+    let now = Utc::now();
+    if end_time <= now {
+        return;
+    }
+    let path = match state_path(handle, "evaluation_reminders.json") {
+        Some(path) => path,
+        None => return,
+    };
+    let mut fired: HashSet<String> = load_json(&path).unwrap_or_default();
+    let mut due = false;
+    for days in config.evaluation_remind_days.iter() {
+        if end_time - chrono::Duration::days(*days as i64) > now {
+            continue;
+        }
+        // a new round or an extended window gets reminded again
+        due |= fired.insert(format!("{}-{}", end_time.to_rfc3339(), days));
+    }
+    if !due {
+        return;
+    }
+
+    let pending = status
+        .courses
+        .iter()
+        .filter(|course| !course.done)
+        .map(|course| course.course_name.as_str())
+        .collect::<Vec<_>>();
+    let mut body = format!(
+        "教学评价将于 {} 截止，未完成评价将无法查询最新成绩。",
+        end_time.with_timezone(&Local).format("%m-%d %H:%M")
+    );
+    if !pending.is_empty() {
+        body.push_str(&format!(
+            "尚有 {} 门课程未评价：{}",
+            pending.len(),
+            pending.join("、")
+        ));
+    }
+    notify(
+        handle,
+        config,
+        NotifyEvent::Evaluation,
+        "教学评价即将截止",
+        &body,
+    )
+    .await;
+
+    if let Err(err) = save_json(&path, &fired) {
+        info!("check_evaluation_reminders: save state failed {}", err);
+    }
+}
+
+pub fn spawn_evaluation_poller(handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = handle.state::<Arc<Mutex<Config>>>().lock().await.clone();
+            let mut zju_assist = handle.state::<Arc<Mutex<ZjuAssist>>>().lock().await.clone();
+            if config.evaluation_poll_interval == 0
+                || config.evaluation_remind_days.is_empty()
+                || !zju_assist.is_login()
+            {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                continue;
+            }
+            info!("evaluation_poller: sync");
+            match load_evaluation_status(&mut zju_assist).await {
+                Ok(status) => {
+                    let _ = handle.emit("evaluation-status", status.clone());
+                    check_evaluation_reminders(&handle, &config, &status).await;
+                }
+                Err(err) => info!("evaluation_poller: check failed {}", err),
+            }
+            tokio::time::sleep(Duration::from_secs(config.evaluation_poll_interval * 60)).await;
+        }
+    });
+}
+
+static ANNOUNCEMENT_SNAPSHOT_LOCK: Mutex<()> = Mutex::const_new(());
+// the full text is on the course page
+const MAX_ANNOUNCEMENT_TEXT: usize = 200;
+
+// notify about announcements not seen before. a course seen for the first time
+// is only recorded, so its old announcements are not sent all at once
+pub async fn check_announcement_changes(
+    handle: &AppHandle,
+    config: &Config,
+    announcements: &HashMap<i64, Vec<CourseAnnouncement>>,
+) {
+    let _lock = ANNOUNCEMENT_SNAPSHOT_LOCK.lock().await;
+    let path = match state_path(handle, "announcement_snapshot.json") {
+        Some(path) => path,
+        None => return,
+    };
+    // course id -> ids of the announcements already seen
+    let mut seen: HashMap<i64, HashSet<i64>> = load_json(&path).unwrap_or_default();
+    let mut fresh = Vec::new();
+    for (course_id, course_announcements) in announcements.iter() {
+        let known = seen.contains_key(course_id);
+        let ids = seen.entry(*course_id).or_default();
+        for announcement in course_announcements.iter() {
+            if ids.insert(announcement.id) && known {
+                fresh.push(announcement);
+            }
+        }
+    }
+    if let Err(err) = save_json(&path, &seen) {
+        info!("check_announcement_changes: save state failed {}", err);
+    }
+    if fresh.is_empty() {
+        return;
+    }
+    info!("check_announcement_changes: {} new", fresh.len());
+
+    for announcement in fresh {
+        let text = html_to_text(&announcement.content).trim().to_string();
+        let mut summary = text.chars().take(MAX_ANNOUNCEMENT_TEXT).collect::<String>();
+        if summary.len() < text.len() {
+            summary.push('…');
+        }
+        notify(
+            handle,
+            config,
+            NotifyEvent::Announcement,
+            "课程通知",
+            &format!(
+                "{}：{}\n{}",
+                announcement.course_name, announcement.title, summary
+            ),
+        )
+        .await;
+    }
+}
+
+pub fn spawn_announcement_poller(handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = handle.state::<Arc<Mutex<Config>>>().lock().await.clone();
+            let zju_assist = handle.state::<Arc<Mutex<ZjuAssist>>>().lock().await.clone();
+            if config.announcement_poll_interval == 0 || !zju_assist.is_login() {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                continue;
+            }
+            info!("announcement_poller: sync");
+            match zju_assist.get_courses().await {
+                Ok(courses) => {
+                    let mut announcements = HashMap::new();
+                    for course in courses.iter() {
+                        let course_id = match course["id"].as_i64() {
+                            Some(course_id) => course_id,
+                            None => continue,
+                        };
+                        let course_name = course["name"].as_str().unwrap_or("").to_string();
+                        // a failed course is left out and keeps its seen ids
+                        match zju_assist.get_course_announcements(course_id).await {
+                            Ok(list) => {
+                                let list = list
+                                    .into_iter()
+                                    .map(|announcement| CourseAnnouncement {
+                                        course_name: course_name.clone(),
+                                        ..announcement
+                                    })
+                                    .collect::<Vec<_>>();
+                                announcements.insert(course_id, list);
+                            }
+                            Err(err) => {
+                                info!("announcement_poller: course {} failed {}", course_id, err)
+                            }
+                        }
+                    }
+                    check_announcement_changes(&handle, &config, &announcements).await;
+                }
+                Err(err) => info!("announcement_poller: get_courses failed {}", err),
+            }
+            tokio::time::sleep(Duration::from_secs(config.announcement_poll_interval * 60)).await;
+        }
+    });
+}
+
+// zdbk is slow enough during course selection, never query it more often
+const MIN_SEAT_POLL_INTERVAL: u64 = 30;
+// failed rounds back off up to this many seconds
+const MAX_SEAT_POLL_INTERVAL: u64 = 600;
+
+static SEAT_SNAPSHOT_LOCK: Mutex<()> = Mutex::const_new(());
+// when zdbk was last queried, shared by the poller and manual checks
+static SEAT_FETCH_TIME: Mutex<Option<Instant>> = Mutex::const_new(None);
+
+pub async fn latest_seats(handle: &AppHandle) -> Vec<SeatInfo> {
+    let _lock = SEAT_SNAPSHOT_LOCK.lock().await;
+    state_path(handle, "seat_snapshot.json")
+        .and_then(|path| load_json::<HashMap<String, SeatInfo>>(&path))
+        .map(|seats| seats.into_values().collect())
+        .unwrap_or_default()
+}
+
+// take the next query slot, or the seconds until it is free
+async fn claim_seat_fetch() -> Result<(), u64> {
+    let mut fetch_time = SEAT_FETCH_TIME.lock().await;
+    if let Some(elapsed) = fetch_time.map(|time| time.elapsed().as_secs()) {
+        if elapsed < MIN_SEAT_POLL_INTERVAL {
+            return Err(MIN_SEAT_POLL_INTERVAL - elapsed);
+        }
+    }
+    *fetch_time = Some(Instant::now());
+    Ok(())
+}
+
+// refused when the last round was too recent
+pub async fn fetch_seats(
+    zju_assist: &mut ZjuAssist,
+    xkkhs: &Vec<String>,
+) -> Result<Vec<SeatInfo>, String> {
+    claim_seat_fetch()
+        .await
+        .map_err(|wait| format!("查询过于频繁，请 {} 秒后再试", wait))?;
+    query_seats(zju_assist, xkkhs).await
+}
+
+// one query per teaching class
+async fn query_seats(
+    zju_assist: &mut ZjuAssist,
+    xkkhs: &[String],
+) -> Result<Vec<SeatInfo>, String> {
+    let mut seats = Vec::new();
+    for (index, xkkh) in xkkhs.iter().enumerate() {
+        if index > 0 {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        match zju_assist.get_course_seat(xkkh).await {
+            Ok(Some(seat)) => seats.push(seat),
+            Ok(None) => info!("fetch_seats: {} not found", xkkh),
+            Err(err) => return Err(err.to_string()),
+        }
+    }
+    Ok(seats)
+}
+
+// notify when the capacity or enrollment of a watched class changes, a class
+// seen for the first time is only recorded
+pub async fn check_seat_changes(handle: &AppHandle, config: &Config, seats: &Vec<SeatInfo>) {
+    let _lock = SEAT_SNAPSHOT_LOCK.lock().await;
+    let path = match state_path(handle, "seat_snapshot.json") {
+        Some(path) => path,
+        None => return,
+    };
+    let mut snapshot: HashMap<String, SeatInfo> = load_json(&path).unwrap_or_default();
+    let changes = seats
+        .iter()
+        .filter_map(
+            |seat| match snapshot.insert(seat.xkkh.clone(), seat.clone()) {
+                Some(old_seat)
+                    if old_seat.capacity != seat.capacity || old_seat.enrolled != seat.enrolled =>
+                {
+                    Some(SeatChange {
+                        seat: seat.clone(),
+                        old_seat,
+                    })
+                }
+                _ => None,
+            },
+        )
+        .collect::<Vec<_>>();
+    // classes no longer watched are dropped
+    snapshot.retain(|xkkh, _| config.seat_watch_xkkhs.contains(xkkh));
+    if let Err(err) = save_json(&path, &snapshot) {
+        info!("check_seat_changes: save state failed {}", err);
+    }
+    if changes.is_empty() {
+        return;
+    }
+    info!("check_seat_changes: {} changes", changes.len());
+    let _ = handle.emit("seat-changes", changes.clone());
+
+    for change in changes.iter() {
+        let title = if free_seats(&change.seat) > 0 && free_seats(&change.old_seat) == 0 {
+            "选课有空余名额"
+        } else {
+            "选课余量变化"
+        };
+        notify(
+            handle,
+            config,
+            NotifyEvent::Seat,
+            title,
+            &format!(
+                "{}，此前已选 {}/{}",
+                describe_seat(&change.seat),
+                change.old_seat.enrolled,
+                change.old_seat.capacity
+            ),
+        )
+        .await;
+    }
+}
+
+pub fn spawn_seat_poller(handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut failures = 0;
+        loop {
+            let config = handle.state::<Arc<Mutex<Config>>>().lock().await.clone();
+            let mut zju_assist = handle.state::<Arc<Mutex<ZjuAssist>>>().lock().await.clone();
+            if !config.seat_watch || config.seat_watch_xkkhs.is_empty() || !zju_assist.is_login() {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                continue;
+            }
+            // a manual check just ran, skip this round instead of counting a failure
+            if let Err(wait) = claim_seat_fetch().await {
+                info!("seat_poller: skip, next query in {}s", wait);
+                tokio::time::sleep(Duration::from_secs(wait)).await;
+                continue;
+            }
+            info!("seat_poller: sync");
+            match query_seats(&mut zju_assist, &config.seat_watch_xkkhs).await {
+                Ok(seats) => {
+                    failures = 0;
+                    check_seat_changes(&handle, &config, &seats).await;
+                }
+                Err(err) => {
+                    failures += 1;
+                    info!("seat_poller: fetch failed {}", err);
+                }
+            }
+            let interval = config.seat_poll_interval.max(MIN_SEAT_POLL_INTERVAL);
+            let interval = (interval << failures.min(5)).min(MAX_SEAT_POLL_INTERVAL.max(interval));
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        }
+    });
+}
@@ -0,0 +1,110 @@
+use log::info;
+use reqwest::{Client, Method, StatusCode};
+
+// reference:
+// https://datatracker.ietf.org/doc/html/rfc4791
+
+#[derive(Clone)]
+pub struct CalDavClient {
+    client: Client,
+    collection_url: String,
+    username: String,
+    password: String,
+}
+
+impl CalDavClient {
+    pub fn new(collection_url: &str, username: &str, password: &str) -> Self {
+        Self {
+            client: Client::new(),
+            collection_url: format!("{}/", collection_url.trim_end_matches('/')),
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    // one resource per uid, so syncing the same todo again overwrites it
+    fn resource_url(&self, uid: &str) -> String {
+        let name = uid
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        format!("{}{}.ics", self.collection_url, name)
+    }
+
+    fn request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
+        let builder = self.client.request(method, url);
+        if self.username.is_empty() {
+            builder
+        } else {
+            builder.basic_auth(&self.username, Some(&self.password))
+        }
+    }
+
+    // make sure the collection exists and is a calendar
+    pub async fn check(&self) -> Result<(), String> {
+        let body = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop><d:resourcetype/></d:prop>
+</d:propfind>"#;
+        let res = self
+            .request(
+                Method::from_bytes(b"PROPFIND").unwrap(),
+                &self.collection_url,
+            )
+            .header("Depth", "0")
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(body)
+            .send()
+            .await
+            .map_err(|err| format!("网络请求失败: {}", err))?;
+        let status = res.status();
+        if status == StatusCode::UNAUTHORIZED {
+            return Err("CalDAV 用户名或密码错误".to_string());
+        }
+        if !status.is_success() {
+            return Err(format!("CalDAV 报错 ({})", status));
+        }
+        let text = res.text().await.map_err(|err| err.to_string())?;
+        if !text.contains("calendar") {
+            return Err("该地址不是日历集合".to_string());
+        }
+        Ok(())
+    }
+
+    pub async fn put(&self, uid: &str, ics: String) -> Result<(), String> {
+        let url = self.resource_url(uid);
+        info!("caldav put: {}", url);
+        let res = self
+            .request(Method::PUT, &url)
+            .header("Content-Type", "text/calendar; charset=utf-8")
+            .body(ics)
+            .send()
+            .await
+            .map_err(|err| err.to_string())?;
+        if !res.status().is_success() {
+            return Err(format!("CalDAV PUT {} 报错 ({})", uid, res.status()));
+        }
+        Ok(())
+    }
+
+    pub async fn delete(&self, uid: &str) -> Result<(), String> {
+        let url = self.resource_url(uid);
+        info!("caldav delete: {}", url);
+        let res = self
+            .request(Method::DELETE, &url)
+            .send()
+            .await
+            .map_err(|err| err.to_string())?;
+        // already gone is fine
+        if !res.status().is_success() && res.status() != StatusCode::NOT_FOUND {
+            return Err(format!("CalDAV DELETE {} 报错 ({})", uid, res.status()));
+        }
+        Ok(())
+    }
+}
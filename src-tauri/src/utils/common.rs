@@ -6,8 +6,9 @@ use miniz_oxide::deflate::{compress_to_vec_zlib, CompressionLevel};
 use num::BigUint;
 use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref};
 use reqwest::Client;
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
-use std::{io::Write, path::Path, time::Instant};
+use std::{path::Path, time::Instant};
 
 // reference:
 // https://github.com/typst/pdf-writer/blob/main/examples/image.rs
@@ -130,62 +131,39 @@ pub async fn measure_latency(client: Client, url: &str) -> Result<u128, reqwest:
     Ok(start.elapsed().as_millis())
 }
 
-pub fn export_todo_ics(
-    todo_list: Vec<Value>,
-    ics_path: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let todo_list = todo_list
-        .iter()
-        .filter(|todo| !todo["end_time"].is_null())
-        .map(|todo| todo.clone())
-        .collect::<Vec<_>>();
-
-    let mut ics_file = std::fs::File::create(ics_path)?;
-
-    // write ics head
-    writeln!(ics_file, "BEGIN:VCALENDAR")?;
-    writeln!(ics_file, "VERSION:2.0")?;
-    writeln!(ics_file, "PRODID:-//Learning in ZJU//EN")?;
-
-    for todo in todo_list.iter() {
-        let end_time = todo["end_time"]
-            .as_str()
-            .unwrap_or("1970-01-01T00:00:00Z")
-            .replace("-", "")
-            .replace(":", "");
-        let course_name = todo["course_name"].as_str().unwrap();
-        let title = todo["title"].as_str().unwrap();
-        let url = format!(
-            "https://courses.zju.edu.cn/course/{}/learning-activity#/{}?view=scores",
-            todo["course_id"].as_i64().unwrap(),
-            todo["id"].as_i64().unwrap()
-        );
+// persisted state of background jobs, missing or broken files are treated as empty
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
 
-        writeln!(ics_file, "BEGIN:VEVENT")?;
-        writeln!(ics_file, "DTSTART:{}", end_time)?;
-        writeln!(ics_file, "DTEND:{}", end_time)?;
-        writeln!(ics_file, "SUMMARY:{}", title)?;
-        writeln!(ics_file, "DESCRIPTION:{}", course_name)?;
-        writeln!(ics_file, "URL:{}", url)?;
-
-        // add alarm 1 hour before the event
-        writeln!(ics_file, "BEGIN:VALARM")?;
-        writeln!(ics_file, "TRIGGER:-PT1H")?;
-        writeln!(ics_file, "ACTION:DISPLAY")?;
-        writeln!(
-            ics_file,
-            "DESCRIPTION:距离 {} 的 {} 截止仅剩 1 小时",
-            course_name, title
-        )?;
-        writeln!(ics_file, "END:VALARM")?;
-
-        writeln!(ics_file, "END:VEVENT")?;
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
     }
+    let content = serde_json::to_string_pretty(value).map_err(|err| err.to_string())?;
+    std::fs::write(path, content).map_err(|err| err.to_string())
+}
 
-    // write ics tail
-    writeln!(ics_file, "END:VCALENDAR")?;
+// zdbk sends the same field as a string or a number, and under different keys
+// depending on the query. the first non-empty one wins
+pub fn json_text(item: &Value, keys: &[&str]) -> String {
+    keys.iter()
+        .find_map(|key| match &item[key] {
+            Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
 
-    Ok(())
+pub fn generate_token() -> String {
+    use rand::{distributions::Alphanumeric, Rng};
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
 }
 
 pub fn format_srt_timestamp(seconds: u64) -> String {
@@ -0,0 +1,113 @@
+use crate::model::{
+    CategoryAudit, CreditAudit, PlanCategory, PlanCourse, ScoreRecord, TrainingPlan,
+};
+use crate::utils::{is_record_passed, json_text};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+// requirement rows of the plan, like {"kclbmc": "通识", "yqzdxf": "28"}
+pub fn parse_plan_category(item: &Value) -> Option<PlanCategory> {
+    let name = json_text(item, &["kclbmc", "xfyqjdmc"]);
+    if name.is_empty() {
+        return None;
+    }
+    Some(PlanCategory {
+        name,
+        required_credit: json_text(item, &["yqzdxf", "yqxf"]).parse().unwrap_or(0.0),
+    })
+}
+
+// used for both plan courses and the completion record
+pub fn parse_plan_course(item: &Value) -> Option<PlanCourse> {
+    let course_code = json_text(item, &["kch", "kcdm"]);
+    let category = json_text(item, &["kclbmc", "xfyqjdmc"]);
+    if course_code.is_empty() || category.is_empty() {
+        return None;
+    }
+    Some(PlanCourse {
+        course_code,
+        course_name: json_text(item, &["kcmc"]),
+        credit: json_text(item, &["xf"]).parse().unwrap_or(0.0),
+        category,
+    })
+}
+
+// course code -> category, the completion record knows where electives were
+// counted, so it wins over the plan when both list a course
+pub fn course_categories(
+    plan: &TrainingPlan,
+    completion: &Vec<PlanCourse>,
+) -> HashMap<String, String> {
+    plan.courses
+        .iter()
+        .chain(completion.iter())
+        .map(|course| (course.course_code.clone(), course.category.clone()))
+        .collect()
+}
+
+pub fn audit_credits(
+    plan: &TrainingPlan,
+    completion: &Vec<PlanCourse>,
+    records: &Vec<ScoreRecord>,
+) -> CreditAudit {
+    let category_of = course_categories(plan, completion);
+
+    let mut categories = plan
+        .categories
+        .iter()
+        .map(|category| CategoryAudit {
+            name: category.name.clone(),
+            required_credit: category.required_credit,
+            completed_credit: 0.0,
+            remaining_credit: 0.0,
+            courses: Vec::new(),
+        })
+        .collect::<Vec<_>>();
+    let mut unmatched = Vec::new();
+
+    // a retaken course earns its credit once
+    let mut seen = HashSet::new();
+    for record in records.iter().filter(|record| is_record_passed(record)) {
+        if !record.course_code.is_empty() && !seen.insert(record.course_code.as_str()) {
+            continue;
+        }
+        let name = match category_of.get(&record.course_code) {
+            Some(name) => name.as_str(),
+            None => {
+                unmatched.push(record.clone());
+                continue;
+            }
+        };
+        let index = match categories.iter().position(|category| category.name == name) {
+            Some(index) => index,
+            None => {
+                categories.push(CategoryAudit {
+                    name: name.to_string(),
+                    required_credit: 0.0,
+                    completed_credit: 0.0,
+                    remaining_credit: 0.0,
+                    courses: Vec::new(),
+                });
+                categories.len() - 1
+            }
+        };
+        categories[index].completed_credit += record.credit;
+        categories[index].courses.push(record.clone());
+    }
+
+    for category in categories.iter_mut() {
+        category.remaining_credit = (category.required_credit - category.completed_credit).max(0.0);
+    }
+    CreditAudit {
+        required_credit: categories
+            .iter()
+            .map(|category| category.required_credit)
+            .sum(),
+        completed_credit: categories
+            .iter()
+            .map(|category| category.completed_credit)
+            .sum(),
+        categories,
+        unmatched,
+    }
+}
@@ -0,0 +1,71 @@
+use crate::model::{DigestUpload, Subject};
+use chrono::{DateTime, Local, Utc};
+use serde_json::Value;
+
+fn section<T>(text: &mut String, title: &str, items: &Vec<T>, line: impl Fn(&T) -> String) {
+    text.push_str(&format!("{}（{}）\n", title, items.len()));
+    if items.is_empty() {
+        text.push_str("- 无\n");
+    }
+    for item in items.iter() {
+        text.push_str(&format!("- {}\n", line(item)));
+    }
+    text.push('\n');
+}
+
+// todos due before `until`, sorted by deadline
+pub fn upcoming_todos(
+    todo_list: &Vec<Value>,
+    now: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Vec<Value> {
+    let mut todos = todo_list
+        .iter()
+        .filter(|todo| {
+            todo["end_time"]
+                .as_str()
+                .and_then(|end_time| end_time.parse::<DateTime<Utc>>().ok())
+                .is_some_and(|end_time| end_time > now && end_time <= until)
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    todos.sort_by_key(|todo| todo["end_time"].as_str().unwrap_or("").to_string());
+    todos
+}
+
+pub fn build_digest(
+    todos: &Vec<Value>,
+    uploads: &Vec<DigestUpload>,
+    subs: &Vec<Subject>,
+) -> String {
+    let mut text = String::new();
+    section(&mut text, "即将截止的待办事项", todos, |todo| {
+        let end_time = todo["end_time"]
+            .as_str()
+            .and_then(|end_time| end_time.parse::<DateTime<Utc>>().ok())
+            .map(|end_time| {
+                end_time
+                    .with_timezone(&Local)
+                    .format("%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        format!(
+            "{} {} · {}",
+            end_time,
+            todo["course_name"].as_str().unwrap_or(""),
+            todo["title"].as_str().unwrap_or("")
+        )
+    });
+    section(&mut text, "新课件", uploads, |upload| {
+        format!("{} · {}", upload.course_name, upload.file_name)
+    });
+    section(&mut text, "新的课程回放", subs, |sub| {
+        format!(
+            "{} · {} · {}",
+            sub.course_name, sub.sub_name, sub.lecturer_name
+        )
+    });
+    text.push_str("此邮件由 ZJU Learning Assistant 自动发送。\n");
+    text
+}
@@ -0,0 +1,71 @@
+use crate::model::Config;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use log::info;
+use std::time::Duration;
+
+fn smtp_transport(config: &Config) -> Result<AsyncSmtpTransport<Tokio1Executor>, String> {
+    let host = config.smtp_host.trim();
+    if host.is_empty() {
+        return Err("未设置 SMTP 服务器".to_string());
+    }
+    let builder = match config.smtp_security.as_str() {
+        "starttls" => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .map_err(|err| err.to_string())?,
+        // plain connection, only meant for a local relay or test sink
+        "none" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+        _ => AsyncSmtpTransport::<Tokio1Executor>::relay(host).map_err(|err| err.to_string())?,
+    };
+    let builder = builder
+        .port(config.smtp_port)
+        .timeout(Some(Duration::from_secs(30)));
+    let builder = if config.smtp_username.is_empty() {
+        builder
+    } else {
+        builder.credentials(Credentials::new(
+            config.smtp_username.clone(),
+            config.smtp_password.clone(),
+        ))
+    };
+    Ok(builder.build())
+}
+
+// the from address defaults to the login name, which is the address for most
+// providers, and mails go to the sender when no recipient is given
+pub async fn send_email(config: &Config, subject: &str, body: &str) -> Result<(), String> {
+    info!("send_email: {}", subject);
+    let from = if config.smtp_from.is_empty() {
+        &config.smtp_username
+    } else {
+        &config.smtp_from
+    };
+    let to = if config.smtp_to.is_empty() {
+        from
+    } else {
+        &config.smtp_to
+    };
+    let mut message = Message::builder()
+        .from(
+            from.parse()
+                .map_err(|_| format!("发件人地址无效：{}", from))?,
+        )
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN);
+    for to in to
+        .split([',', ';'])
+        .map(|to| to.trim())
+        .filter(|to| !to.is_empty())
+    {
+        message = message.to(to.parse().map_err(|_| format!("收件人地址无效：{}", to))?);
+    }
+    let message = message
+        .body(body.to_string())
+        .map_err(|err| err.to_string())?;
+
+    smtp_transport(config)?
+        .send(message)
+        .await
+        .map_err(|err| format!("发送邮件失败：{}", err))?;
+    Ok(())
+}
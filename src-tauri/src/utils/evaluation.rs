@@ -0,0 +1,42 @@
+use crate::model::EvaluationCourse;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeZone};
+use regex::Regex;
+use serde_json::Value;
+
+// the evaluation page states its window like
+// "评价时间：2024-12-23 00:00:00 至 2025-01-05 23:59:59", always in China time
+pub fn parse_evaluation_window(
+    text: &str,
+) -> Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+    let date = r"(\d{4})[-/年](\d{1,2})[-/月](\d{1,2})日?(?:\s*(\d{1,2}):(\d{2})(?::(\d{2}))?)?";
+    let re = Regex::new(&format!(r"{}\s*(?:至|到|~|—|-)\s*{}", date, date)).unwrap();
+    let caps = re.captures(text)?;
+    let china = FixedOffset::east_opt(8 * 3600)?;
+    // a date without time covers the whole day
+    let datetime = |offset: usize, end_of_day: bool| {
+        let num = |i: usize| {
+            caps.get(offset + i)
+                .and_then(|m| m.as_str().parse::<u32>().ok())
+        };
+        let date = NaiveDate::from_ymd_opt(num(0)? as i32, num(1)?, num(2)?)?;
+        let time = match num(3) {
+            Some(hour) => NaiveTime::from_hms_opt(hour, num(4)?, num(5).unwrap_or(0))?,
+            None if end_of_day => NaiveTime::from_hms_opt(23, 59, 59)?,
+            None => NaiveTime::MIN,
+        };
+        china.from_local_datetime(&date.and_time(time)).single()
+    };
+    Some((datetime(1, false)?, datetime(7, true)?))
+}
+
+pub fn parse_evaluation_course(item: &Value) -> EvaluationCourse {
+    let text = |key: &str| item[key].as_str().unwrap_or("").trim().to_string();
+    // tjzt is 1 once submitted, tjztmc reads like 已评完 or 未评
+    let state = text("tjztmc");
+    EvaluationCourse {
+        id: text("jxb_id"),
+        course_name: text("kcmc"),
+        teacher: text("jzgmc"),
+        done: text("tjzt") == "1" || state.starts_with('已'),
+    }
+}
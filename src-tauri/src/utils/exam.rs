@@ -0,0 +1,78 @@
+use crate::model::{Exam, ExamKind};
+use crate::utils::{ics_datetime, IcsCalendar};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc};
+use regex::Regex;
+
+// zdbk shows exam time like "2024年01月15日(08:00-10:00)", always in China time
+pub fn parse_exam_time(text: &str) -> Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+    let re = Regex::new(
+        r"(\d{4})年(\d{1,2})月(\d{1,2})日\D*(\d{1,2}):(\d{2})\s*[-~至]\s*(\d{1,2}):(\d{2})",
+    )
+    .unwrap();
+    let caps = re.captures(text)?;
+    let num = |i: usize| caps[i].parse::<u32>().ok();
+    let date = NaiveDate::from_ymd_opt(caps[1].parse().ok()?, num(2)?, num(3)?)?;
+    let start = NaiveTime::from_hms_opt(num(4)?, num(5)?, 0)?;
+    let end = NaiveTime::from_hms_opt(num(6)?, num(7)?, 0)?;
+    let china = FixedOffset::east_opt(8 * 3600)?;
+    Some((
+        china.from_local_datetime(&date.and_time(start)).single()?,
+        china.from_local_datetime(&date.and_time(end)).single()?,
+    ))
+}
+
+pub fn exam_kind_name(kind: &ExamKind) -> &'static str {
+    match kind {
+        ExamKind::Midterm => "期中考试",
+        ExamKind::Final => "期末考试",
+    }
+}
+
+pub fn exam_uid(exam: &Exam) -> String {
+    format!("exam-{}@zdbk.zju.edu.cn", exam.id)
+}
+
+pub fn exams_to_ics(exams: &Vec<Exam>, alarms: &Vec<u64>) -> String {
+    let now = ics_datetime(&Utc::now());
+    let mut calendar = IcsCalendar::new("浙江大学考试安排");
+    for exam in exams.iter() {
+        let start_time = exam
+            .start_time
+            .as_ref()
+            .and_then(|time| time.parse::<DateTime<Utc>>().ok());
+        let end_time = exam
+            .end_time
+            .as_ref()
+            .and_then(|time| time.parse::<DateTime<Utc>>().ok());
+        // not arranged yet
+        let (start_time, end_time) = match start_time.zip(end_time) {
+            Some(time) => time,
+            None => continue,
+        };
+        let summary = format!("{}（{}）", exam.course_name, exam_kind_name(&exam.kind));
+        let location = match (&exam.location, &exam.seat) {
+            (Some(location), Some(seat)) => format!("{} 座位号 {}", location, seat),
+            (Some(location), None) => location.clone(),
+            (None, _) => "".to_string(),
+        };
+
+        calendar.begin("VEVENT");
+        calendar.text("UID", &exam_uid(exam));
+        calendar.property("DTSTAMP", &now);
+        calendar.text("SUMMARY", &summary);
+        if !location.is_empty() {
+            calendar.text("LOCATION", &location);
+        }
+        calendar.text(
+            "DESCRIPTION",
+            &format!("{} {}", exam.course_code, exam.semester),
+        );
+        calendar.property("DTSTART", &ics_datetime(&start_time));
+        calendar.property("DTEND", &ics_datetime(&end_time));
+        for minutes in alarms.iter() {
+            calendar.alarm(*minutes, &summary);
+        }
+        calendar.end("VEVENT");
+    }
+    calendar.finish()
+}
@@ -0,0 +1,160 @@
+use crate::model::Topic;
+use chrono::{DateTime, Local, Utc};
+use regex::Regex;
+use serde_json::Value;
+
+fn format_time(time: &str) -> String {
+    match time.parse::<DateTime<Utc>>() {
+        Ok(time) => time
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string(),
+        Err(_) => time.to_string(),
+    }
+}
+
+fn upload_names(uploads: &Vec<Value>) -> Vec<String> {
+    uploads
+        .iter()
+        .filter_map(|upload| upload["name"].as_str().map(|name| name.to_string()))
+        .collect()
+}
+
+// rich text from Learning in ZJU is html, keep only the text with line breaks
+pub fn html_to_text(html: &str) -> String {
+    let re_break = Regex::new(r"(?i)<br\s*/?>|</p>|</div>|</li>").unwrap();
+    let re_tag = Regex::new(r"(?s)<[^>]*>").unwrap();
+    let text = re_break.replace_all(html, "\n");
+    let text = re_tag.replace_all(&text, "");
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.lines()
+        .map(|line| line.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+// the exported page is opened from disk, drop anything that could run code
+fn sanitize_html(html: &str) -> String {
+    let mut html = html.to_string();
+    for tag in [
+        "script", "style", "iframe", "frame", "frameset", "object", "embed", "applet", "noscript",
+        "template",
+    ] {
+        let re = Regex::new(&format!(r"(?is)<{0}\b.*?</{0}\s*>|</?{0}\b[^>]*>", tag)).unwrap();
+        html = re.replace_all(&html, "").to_string();
+    }
+    let re_void = Regex::new(r"(?i)<(base|link|meta)\b[^>]*>").unwrap();
+    html = re_void.replace_all(&html, "").to_string();
+
+    // event handlers and script urls only matter inside tags
+    let re_tag = Regex::new(r"(?s)<[a-zA-Z][^>]*>").unwrap();
+    let re_handler = Regex::new(r#"(?i)\s+on[a-z]+\s*=\s*("[^"]*"|'[^']*'|[^\s>]+)"#).unwrap();
+    let re_url = Regex::new(
+        r#"(?i)\s+(href|src|action|formaction|xlink:href)\s*=\s*("\s*(javascript|vbscript|data:text/html)[^"]*"|'\s*(javascript|vbscript|data:text/html)[^']*'|(javascript|vbscript|data:text/html)[^\s>]*)"#,
+    )
+    .unwrap();
+    re_tag
+        .replace_all(&html, |caps: &regex::Captures| {
+            let tag = re_handler.replace_all(&caps[0], "");
+            re_url.replace_all(&tag, "").to_string()
+        })
+        .to_string()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace("&", "&amp;")
+        .replace("<", "&lt;")
+        .replace(">", "&gt;")
+        .replace("\"", "&quot;")
+}
+
+pub fn topic_to_markdown(topic: &Topic) -> String {
+    let mut md = String::new();
+    md.push_str(&format!("# {}\n\n", topic.title));
+    md.push_str(&format!(
+        "> {} 发表于 {}\n\n",
+        topic.author,
+        format_time(&topic.created_at)
+    ));
+    md.push_str(&html_to_text(&topic.content));
+    md.push_str("\n\n");
+    let attachments = upload_names(&topic.uploads);
+    if !attachments.is_empty() {
+        md.push_str("**附件**\n\n");
+        for name in attachments {
+            md.push_str(&format!("- {}\n", name));
+        }
+        md.push_str("\n");
+    }
+
+    md.push_str(&format!("## 回复（{}）\n\n", topic.replies.len()));
+    for reply in topic.replies.iter() {
+        md.push_str(&format!(
+            "### {} · {}\n\n",
+            reply.author,
+            format_time(&reply.created_at)
+        ));
+        md.push_str(&html_to_text(&reply.content));
+        md.push_str("\n\n");
+        for name in upload_names(&reply.uploads) {
+            md.push_str(&format!("- 附件：{}\n", name));
+        }
+    }
+    md
+}
+
+pub fn topic_to_html(topic: &Topic) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    // scripts are blocked again in case the sanitizer missed something
+    html.push_str(
+        "<meta http-equiv=\"Content-Security-Policy\" content=\"script-src 'none'; object-src 'none'; frame-src 'none'\">\n",
+    );
+    html.push_str(&format!("<title>{}</title>\n", escape_html(&topic.title)));
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", escape_html(&topic.title)));
+    html.push_str(&format!(
+        "<p><em>{} 发表于 {}</em></p>\n",
+        escape_html(&topic.author),
+        format_time(&topic.created_at)
+    ));
+    // the content is already html
+    html.push_str(&format!("<div>{}</div>\n", sanitize_html(&topic.content)));
+    let attachments = upload_names(&topic.uploads);
+    if !attachments.is_empty() {
+        html.push_str("<p><strong>附件</strong></p>\n<ul>\n");
+        for name in attachments {
+            html.push_str(&format!("<li>{}</li>\n", escape_html(&name)));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    html.push_str(&format!("<h2>回复（{}）</h2>\n", topic.replies.len()));
+    for reply in topic.replies.iter() {
+        html.push_str("<hr>\n");
+        html.push_str(&format!(
+            "<h3>{} · {}</h3>\n",
+            escape_html(&reply.author),
+            format_time(&reply.created_at)
+        ));
+        html.push_str(&format!("<div>{}</div>\n", sanitize_html(&reply.content)));
+        let attachments = upload_names(&reply.uploads);
+        if !attachments.is_empty() {
+            html.push_str("<ul>\n");
+            for name in attachments {
+                html.push_str(&format!("<li>附件：{}</li>\n", escape_html(&name)));
+            }
+            html.push_str("</ul>\n");
+        }
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
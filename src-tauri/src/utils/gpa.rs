@@ -0,0 +1,346 @@
+use crate::model::{GpaBreakdown, GpaOptions, GpaSummary, ScoreRecord, SemesterGpa};
+use std::collections::BTreeMap;
+
+// a passed makeup exam is recorded as 60
+const MAKEUP_PASS_SCORE: f64 = 60.0;
+
+// five-level grades as percentages, following the zdbk conversion
+fn level_score(score: &str) -> Option<f64> {
+    match score {
+        "优秀" | "优" => Some(95.0),
+        "良好" | "良" => Some(85.0),
+        "中等" | "中" => Some(75.0),
+        "及格" => Some(65.0),
+        "不及格" => Some(0.0),
+        _ => None,
+    }
+}
+
+// None for records without a usable score, like 缓考 or 免修
+pub fn percentage_score(record: &ScoreRecord) -> Option<f64> {
+    if record.makeup_replaced {
+        return Some(MAKEUP_PASS_SCORE);
+    }
+    let score = record.score.trim();
+    match score.parse::<f64>() {
+        Ok(score) => Some(score),
+        Err(_) => level_score(score),
+    }
+}
+
+// the 4.3 table printed on zju english transcripts
+pub fn grade_point_4_3(score: f64) -> f64 {
+    let score = score.round();
+    match score {
+        s if s >= 95.0 => 4.3,
+        s if s >= 92.0 => 4.2,
+        s if s >= 89.0 => 4.1,
+        s if s >= 86.0 => 4.0,
+        s if s >= 83.0 => 3.9,
+        s if s >= 80.0 => 3.6,
+        s if s >= 77.0 => 3.3,
+        s if s >= 74.0 => 3.0,
+        s if s >= 71.0 => 2.7,
+        s if s >= 68.0 => 2.4,
+        s if s >= 65.0 => 2.1,
+        s if s >= 62.0 => 1.8,
+        s if s >= 60.0 => 1.5,
+        _ => 0.0,
+    }
+}
+
+// the common chinese 4.0 table used by most application services
+pub fn grade_point_4_0(score: f64) -> f64 {
+    let score = score.round();
+    match score {
+        s if s >= 90.0 => 4.0,
+        s if s >= 85.0 => 3.7,
+        s if s >= 82.0 => 3.3,
+        s if s >= 78.0 => 3.0,
+        s if s >= 75.0 => 2.7,
+        s if s >= 72.0 => 2.3,
+        s if s >= 68.0 => 2.0,
+        s if s >= 64.0 => 1.5,
+        s if s >= 60.0 => 1.0,
+        _ => 0.0,
+    }
+}
+
+fn is_included(record: &ScoreRecord, options: &GpaOptions) -> bool {
+    if !record.counted || record.credit <= 0.0 || percentage_score(record).is_none() {
+        return false;
+    }
+    if options.excluded_course_codes.contains(&record.course_code) {
+        return false;
+    }
+    match &options.major_course_codes {
+        Some(codes) => codes.contains(&record.course_code),
+        None => true,
+    }
+}
+
+pub fn gpa_summary(records: &Vec<&ScoreRecord>) -> GpaSummary {
+    let mut summary = GpaSummary::default();
+    let (mut gp, mut gp_4_3, mut gp_4_0, mut total_score) = (0.0, 0.0, 0.0, 0.0);
+    for record in records.iter() {
+        let score = match percentage_score(record) {
+            Some(score) => score,
+            None => continue,
+        };
+        summary.course_count += 1;
+        summary.credit += record.credit;
+        gp += record.effective_grade_point * record.credit;
+        gp_4_3 += grade_point_4_3(score) * record.credit;
+        gp_4_0 += grade_point_4_0(score) * record.credit;
+        total_score += score * record.credit;
+    }
+    if summary.credit > 0.0 {
+        summary.gpa = gp / summary.credit;
+        summary.gpa_4_3 = gp_4_3 / summary.credit;
+        summary.gpa_4_0 = gp_4_0 / summary.credit;
+        summary.average_score = total_score / summary.credit;
+    }
+    summary
+}
+
+// records should already have gone through apply_score_rules
+pub fn gpa_breakdown(records: &Vec<ScoreRecord>, options: &GpaOptions) -> GpaBreakdown {
+    let (included, skipped): (Vec<&ScoreRecord>, Vec<&ScoreRecord>) = records
+        .iter()
+        .partition(|record| is_included(record, options));
+
+    // semester strings like 2023-2024-1 sort in order
+    let mut semesters: BTreeMap<&str, Vec<&ScoreRecord>> = BTreeMap::new();
+    for record in included.iter() {
+        semesters
+            .entry(record.semester.as_str())
+            .or_default()
+            .push(record);
+    }
+
+    GpaBreakdown {
+        cumulative: gpa_summary(&included),
+        semesters: semesters
+            .into_iter()
+            .map(|(semester, records)| SemesterGpa {
+                semester: semester.to_string(),
+                summary: gpa_summary(&records),
+            })
+            .collect(),
+        skipped: skipped.into_iter().cloned().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parse_score_records;
+    use serde_json::json;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    fn record(score: &str) -> ScoreRecord {
+        ScoreRecord {
+            xkkh: "(2023-2024-1)-011A0010-0001".to_string(),
+            course_code: "011A0010".to_string(),
+            semester: "2023-2024-1".to_string(),
+            course_name: "高等数学".to_string(),
+            score: score.to_string(),
+            makeup_score: None,
+            credit: 2.0,
+            grade_point: 0.0,
+            effective_grade_point: 0.0,
+            makeup_replaced: false,
+            counted: true,
+        }
+    }
+
+    // two semesters with a makeup, a pass/fail course and a retake
+    fn sample_transcript() -> Vec<ScoreRecord> {
+        let items = json!([
+            { "xkkh": "(2022-2023-1)-011A0010-0001", "kcmc": "高等数学", "cj": "92", "xf": "5", "jd": "4.5" },
+            { "xkkh": "(2022-2023-1)-021B0020-0001", "kcmc": "大学物理", "cj": "55", "bkcj": "70", "xf": "2", "jd": "0" },
+            { "xkkh": "(2022-2023-1)-031C0030-0001", "kcmc": "体育", "cj": "合格", "xf": "1", "jd": "0" },
+            { "xkkh": "(2022-2023-1)-041D0040-0001", "kcmc": "程序设计", "cj": "58", "xf": "3", "jd": "0" },
+            { "xkkh": "(2022-2023-2)-041D0040-0002", "kcmc": "程序设计", "cj": "良好", "xf": "3", "jd": "3.9" },
+            { "xkkh": "(2022-2023-2)-051E0050-0001", "kcmc": "数据结构", "cj": "优秀", "xf": "2", "jd": "4.8" },
+        ]);
+        parse_score_records(items.as_array().unwrap())
+    }
+
+    #[test]
+    fn grade_point_4_3_boundaries() {
+        let cases = [
+            (100.0, 4.3),
+            (95.0, 4.3),
+            (94.5, 4.3),
+            (94.4, 4.2),
+            (92.0, 4.2),
+            (91.0, 4.1),
+            (89.0, 4.1),
+            (88.0, 4.0),
+            (86.0, 4.0),
+            (85.0, 3.9),
+            (83.0, 3.9),
+            (82.0, 3.6),
+            (80.0, 3.6),
+            (79.0, 3.3),
+            (77.0, 3.3),
+            (76.0, 3.0),
+            (74.0, 3.0),
+            (73.0, 2.7),
+            (71.0, 2.7),
+            (70.0, 2.4),
+            (68.0, 2.4),
+            (67.0, 2.1),
+            (65.0, 2.1),
+            (64.0, 1.8),
+            (62.0, 1.8),
+            (61.0, 1.5),
+            (60.0, 1.5),
+            (59.5, 1.5),
+            (59.4, 0.0),
+            (0.0, 0.0),
+        ];
+        for (score, expected) in cases {
+            assert_eq!(grade_point_4_3(score), expected, "score {}", score);
+        }
+    }
+
+    #[test]
+    fn grade_point_4_0_boundaries() {
+        let cases = [
+            (100.0, 4.0),
+            (90.0, 4.0),
+            (89.5, 4.0),
+            (89.4, 3.7),
+            (85.0, 3.7),
+            (84.0, 3.3),
+            (82.0, 3.3),
+            (81.0, 3.0),
+            (78.0, 3.0),
+            (77.0, 2.7),
+            (75.0, 2.7),
+            (74.0, 2.3),
+            (72.0, 2.3),
+            (71.0, 2.0),
+            (68.0, 2.0),
+            (67.0, 1.5),
+            (64.0, 1.5),
+            (63.0, 1.0),
+            (60.0, 1.0),
+            (59.4, 0.0),
+        ];
+        for (score, expected) in cases {
+            assert_eq!(grade_point_4_0(score), expected, "score {}", score);
+        }
+    }
+
+    #[test]
+    fn five_level_grades_are_converted() {
+        let cases = [
+            ("优秀", Some(95.0)),
+            ("优", Some(95.0)),
+            ("良好", Some(85.0)),
+            ("良", Some(85.0)),
+            ("中等", Some(75.0)),
+            ("中", Some(75.0)),
+            ("及格", Some(65.0)),
+            ("不及格", Some(0.0)),
+            (" 88 ", Some(88.0)),
+            ("缓考", None),
+            ("", None),
+        ];
+        for (score, expected) in cases {
+            assert_eq!(
+                percentage_score(&record(score)),
+                expected,
+                "score {}",
+                score
+            );
+        }
+    }
+
+    #[test]
+    fn passed_makeup_counts_as_60() {
+        let mut record = record("45");
+        record.makeup_score = Some("80".to_string());
+        record.makeup_replaced = true;
+        assert_eq!(percentage_score(&record), Some(MAKEUP_PASS_SCORE));
+    }
+
+    #[test]
+    fn pass_fail_courses_are_skipped() {
+        let records = sample_transcript();
+        let breakdown = gpa_breakdown(&records, &GpaOptions::default());
+        let skipped = breakdown
+            .skipped
+            .iter()
+            .map(|record| record.xkkh.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            skipped,
+            ["(2022-2023-1)-031C0030-0001", "(2022-2023-1)-041D0040-0001"]
+        );
+        assert_eq!(breakdown.cumulative.course_count, 4);
+    }
+
+    #[test]
+    fn breakdown_of_sample_transcript() {
+        let records = sample_transcript();
+        let breakdown = gpa_breakdown(&records, &GpaOptions::default());
+
+        let semesters = breakdown
+            .semesters
+            .iter()
+            .map(|semester| semester.semester.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(semesters, ["2022-2023-1", "2022-2023-2"]);
+
+        // 高等数学 92 and 大学物理 passed in the makeup
+        let first = &breakdown.semesters[0].summary;
+        assert_eq!(first.course_count, 2);
+        assert_close(first.credit, 7.0);
+        assert_close(first.gpa, 25.5 / 7.0);
+        assert_close(first.gpa_4_3, 24.0 / 7.0);
+        assert_close(first.gpa_4_0, 22.0 / 7.0);
+        assert_close(first.average_score, 580.0 / 7.0);
+
+        // the 良好 retake of 程序设计 and 数据结构 优秀
+        let second = &breakdown.semesters[1].summary;
+        assert_eq!(second.course_count, 2);
+        assert_close(second.credit, 5.0);
+        assert_close(second.gpa, 4.26);
+        assert_close(second.gpa_4_3, 4.06);
+        assert_close(second.gpa_4_0, 3.82);
+        assert_close(second.average_score, 89.0);
+
+        let cumulative = &breakdown.cumulative;
+        assert_eq!(cumulative.course_count, 4);
+        assert_close(cumulative.credit, 12.0);
+        assert_close(cumulative.gpa, 3.9);
+        assert_close(cumulative.gpa_4_3, 44.3 / 12.0);
+        assert_close(cumulative.gpa_4_0, 41.1 / 12.0);
+        assert_close(cumulative.average_score, 1025.0 / 12.0);
+    }
+
+    #[test]
+    fn options_select_courses() {
+        let records = sample_transcript();
+        let options = GpaOptions {
+            major_course_codes: Some(vec!["011A0010".to_string(), "041D0040".to_string()]),
+            excluded_course_codes: vec!["041D0040".to_string()],
+        };
+        let breakdown = gpa_breakdown(&records, &options);
+        assert_eq!(breakdown.cumulative.course_count, 1);
+        assert_close(breakdown.cumulative.gpa, 4.5);
+        assert_eq!(breakdown.semesters.len(), 1);
+    }
+}
@@ -0,0 +1,395 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+// reference:
+// https://datatracker.ietf.org/doc/html/rfc5545
+
+pub fn escape_ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+        .replace('\r', "\\n")
+}
+
+// lines longer than 75 octets are split, continuation lines start with a space
+pub fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded
+}
+
+pub fn ics_datetime(time: &DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+pub struct IcsCalendar {
+    content: String,
+}
+
+impl IcsCalendar {
+    pub fn new(name: &str) -> Self {
+        let mut calendar = Self {
+            content: String::new(),
+        };
+        calendar.property("BEGIN", "VCALENDAR");
+        calendar.property("VERSION", "2.0");
+        calendar.property("PRODID", "-//Learning in ZJU//EN");
+        calendar.property("CALSCALE", "GREGORIAN");
+        calendar.text("X-WR-CALNAME", name);
+        calendar
+    }
+
+    pub fn begin(&mut self, component: &str) -> &mut Self {
+        self.property("BEGIN", component)
+    }
+
+    pub fn end(&mut self, component: &str) -> &mut Self {
+        self.property("END", component)
+    }
+
+    // value is written as is, use `text` for TEXT values
+    pub fn property(&mut self, name: &str, value: &str) -> &mut Self {
+        self.content
+            .push_str(&fold_ics_line(&format!("{}:{}", name, value)));
+        self.content.push_str("\r\n");
+        self
+    }
+
+    pub fn text(&mut self, name: &str, value: &str) -> &mut Self {
+        self.property(name, &escape_ics_text(value))
+    }
+
+    pub fn alarm(&mut self, minutes: u64, description: &str) -> &mut Self {
+        self.begin("VALARM");
+        self.property("TRIGGER", &format!("-PT{}M", minutes));
+        self.property("ACTION", "DISPLAY");
+        self.text("DESCRIPTION", description);
+        self.end("VALARM")
+    }
+
+    // China has no daylight saving time, a fixed offset is enough
+    pub fn shanghai_timezone(&mut self) -> &mut Self {
+        self.begin("VTIMEZONE");
+        self.property("TZID", "Asia/Shanghai");
+        self.begin("STANDARD");
+        self.property("DTSTART", "19700101T000000");
+        self.property("TZOFFSETFROM", "+0800");
+        self.property("TZOFFSETTO", "+0800");
+        self.property("TZNAME", "CST");
+        self.end("STANDARD");
+        self.end("VTIMEZONE")
+    }
+
+    pub fn finish(mut self) -> String {
+        self.property("END", "VCALENDAR");
+        self.content
+    }
+}
+
+pub fn todo_uid(todo: &Value) -> String {
+    format!(
+        "todo-{}-{}@courses.zju.edu.cn",
+        todo["course_id"].as_i64().unwrap_or(0),
+        todo["id"].as_i64().unwrap_or(0)
+    )
+}
+
+pub fn todo_url(todo: &Value) -> String {
+    format!(
+        "https://courses.zju.edu.cn/course/{}/learning-activity#/{}?view=scores",
+        todo["course_id"].as_i64().unwrap_or(0),
+        todo["id"].as_i64().unwrap_or(0)
+    )
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct IcsSequence {
+    pub end_time: Option<String>,
+    pub sequence: u32,
+}
+
+// bump SEQUENCE of todos whose deadline changed since the last export, so
+// calendar apps update the existing entry instead of ignoring it
+pub fn update_ics_sequences(sequences: &mut HashMap<String, IcsSequence>, todo_list: &Vec<Value>) {
+    for todo in todo_list.iter() {
+        let end_time = todo["end_time"].as_str().map(|s| s.to_string());
+        let entry = sequences.entry(todo_uid(todo)).or_insert(IcsSequence {
+            end_time: end_time.clone(),
+            sequence: 0,
+        });
+        if entry.end_time != end_time {
+            entry.end_time = end_time;
+            entry.sequence += 1;
+        }
+    }
+}
+
+// reminder offsets as shown to the user, shared with the deadline reminders
+pub(crate) fn format_offset(minutes: u64) -> String {
+    if minutes % (24 * 60) == 0 {
+        format!("{} 天", minutes / (24 * 60))
+    } else if minutes % 60 == 0 {
+        format!("{} 小时", minutes / 60)
+    } else {
+        format!("{} 分钟", minutes)
+    }
+}
+
+fn write_todo(
+    calendar: &mut IcsCalendar,
+    todo: &Value,
+    alarms: &Vec<u64>,
+    sequence: u32,
+    now: &str,
+) {
+    let course_name = todo["course_name"].as_str().unwrap_or("");
+    let title = todo["title"].as_str().unwrap_or("");
+    let end_time = todo["end_time"]
+        .as_str()
+        .and_then(|end_time| end_time.parse::<DateTime<Utc>>().ok());
+
+    // todos with a deadline are events at the deadline, others are tasks
+    let component = if end_time.is_some() {
+        "VEVENT"
+    } else {
+        "VTODO"
+    };
+    calendar.begin(component);
+    calendar.text("UID", &todo_uid(todo));
+    calendar.property("DTSTAMP", now);
+    calendar.property("SEQUENCE", &sequence.to_string());
+    calendar.text("SUMMARY", title);
+    calendar.text("DESCRIPTION", course_name);
+    calendar.property("URL", &todo_url(todo));
+    match end_time {
+        Some(end_time) => {
+            calendar.property("DTSTART", &ics_datetime(&end_time));
+            // a deadline has no length, say so instead of leaving it to the client
+            calendar.property("DURATION", "PT0S");
+            for minutes in alarms.iter() {
+                calendar.alarm(
+                    *minutes,
+                    &format!(
+                        "距离 {} 的 {} 截止仅剩 {}",
+                        course_name,
+                        title,
+                        format_offset(*minutes)
+                    ),
+                );
+            }
+        }
+        None => {
+            calendar.property("STATUS", "NEEDS-ACTION");
+        }
+    }
+    calendar.end(component);
+}
+
+pub fn todo_list_to_ics(
+    todo_list: &Vec<Value>,
+    alarms: &Vec<u64>,
+    sequences: &HashMap<String, IcsSequence>,
+) -> String {
+    let now = ics_datetime(&Utc::now());
+    let mut calendar = IcsCalendar::new("学在浙大待办事项");
+    for todo in todo_list.iter() {
+        let sequence = sequences.get(&todo_uid(todo)).map_or(0, |s| s.sequence);
+        write_todo(&mut calendar, todo, alarms, sequence, &now);
+    }
+    calendar.finish()
+}
+
+// a calendar object resource holds exactly one component, used by CalDAV
+pub fn todo_to_ics(todo: &Value, alarms: &Vec<u64>, sequence: u32) -> String {
+    let now = ics_datetime(&Utc::now());
+    let mut calendar = IcsCalendar::new("学在浙大待办事项");
+    write_todo(&mut calendar, todo, alarms, sequence, &now);
+    calendar.finish()
+}
+
+pub fn export_todo_ics(
+    todo_list: Vec<Value>,
+    ics_path: &str,
+    alarms: &Vec<u64>,
+    sequences: &HashMap<String, IcsSequence>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(ics_path, todo_list_to_ics(&todo_list, alarms, sequences))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ical::parser::ical::component::IcalCalendar;
+    use ical::property::Property;
+    use serde_json::json;
+    use std::io::BufReader;
+
+    fn parse(content: &str) -> IcalCalendar {
+        ical::IcalParser::new(BufReader::new(content.as_bytes()))
+            .next()
+            .expect("no calendar")
+            .expect("invalid calendar")
+    }
+
+    fn value<'a>(properties: &'a [Property], name: &str) -> Option<&'a str> {
+        properties
+            .iter()
+            .find(|property| property.name == name)
+            .and_then(|property| property.value.as_deref())
+    }
+
+    // the parser keeps TEXT values escaped
+    fn unescape(text: &str) -> String {
+        let mut unescaped = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                unescaped.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') | Some('N') => unescaped.push('\n'),
+                Some(c) => unescaped.push(c),
+                None => unescaped.push('\\'),
+            }
+        }
+        unescaped
+    }
+
+    fn todo(id: i64, title: &str, end_time: Option<&str>) -> Value {
+        json!({
+            "id": id,
+            "course_id": 100,
+            "course_name": "数据结构基础",
+            "title": title,
+            "end_time": end_time,
+        })
+    }
+
+    #[test]
+    fn uid_and_sequence_are_stable() {
+        let todo_list = vec![todo(1, "作业一", Some("2025-03-01T15:59:00Z"))];
+        let mut sequences = HashMap::new();
+        update_ics_sequences(&mut sequences, &todo_list);
+        update_ics_sequences(&mut sequences, &todo_list);
+
+        let first = parse(&todo_list_to_ics(&todo_list, &vec![], &sequences));
+        let second = parse(&todo_list_to_ics(&todo_list, &vec![], &sequences));
+        for calendar in [&first, &second] {
+            let event = &calendar.events[0];
+            assert_eq!(
+                value(&event.properties, "UID"),
+                Some("todo-100-1@courses.zju.edu.cn")
+            );
+            assert_eq!(value(&event.properties, "SEQUENCE"), Some("0"));
+        }
+    }
+
+    #[test]
+    fn sequence_is_bumped_when_deadline_changes() {
+        let mut sequences = HashMap::new();
+        update_ics_sequences(
+            &mut sequences,
+            &vec![todo(1, "作业一", Some("2025-03-01T15:59:00Z"))],
+        );
+        let todo_list = vec![todo(1, "作业一", Some("2025-03-08T15:59:00Z"))];
+        update_ics_sequences(&mut sequences, &todo_list);
+
+        let calendar = parse(&todo_list_to_ics(&todo_list, &vec![], &sequences));
+        let event = &calendar.events[0];
+        assert_eq!(value(&event.properties, "SEQUENCE"), Some("1"));
+        assert_eq!(
+            value(&event.properties, "DTSTART"),
+            Some("20250308T155900Z")
+        );
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        assert_eq!(
+            escape_ics_text("a\\b;c,d\ne\r\nf"),
+            "a\\\\b\\;c\\,d\\ne\\nf"
+        );
+
+        let title = "实验\\报告; 第一部分, 提交\n要求";
+        let calendar = parse(&todo_list_to_ics(
+            &vec![todo(1, title, Some("2025-03-01T15:59:00Z"))],
+            &vec![],
+            &HashMap::new(),
+        ));
+        let summary = value(&calendar.events[0].properties, "SUMMARY").unwrap();
+        assert_eq!(unescape(summary), title);
+    }
+
+    #[test]
+    fn long_lines_are_folded_between_characters() {
+        let title = "面向对象程序设计课程大作业第二阶段提交：图形界面与网络通信模块的设计与实现";
+        let content = todo_list_to_ics(
+            &vec![todo(1, title, Some("2025-03-01T15:59:00Z"))],
+            &vec![],
+            &HashMap::new(),
+        );
+        for line in content.split("\r\n") {
+            assert!(line.len() <= 75, "line too long: {}", line);
+        }
+        assert!(content.contains("\r\n "));
+
+        let calendar = parse(&content);
+        let summary = value(&calendar.events[0].properties, "SUMMARY").unwrap();
+        assert_eq!(unescape(summary), title);
+    }
+
+    #[test]
+    fn deadline_becomes_event_with_alarms() {
+        let calendar = parse(&todo_list_to_ics(
+            &vec![todo(1, "作业一", Some("2025-03-01T15:59:00Z"))],
+            &vec![60, 24 * 60],
+            &HashMap::new(),
+        ));
+        assert_eq!(calendar.events.len(), 1);
+        assert!(calendar.todos.is_empty());
+        let event = &calendar.events[0];
+        assert_eq!(
+            value(&event.properties, "DTSTART"),
+            Some("20250301T155900Z")
+        );
+        assert_eq!(value(&event.properties, "DURATION"), Some("PT0S"));
+        assert_eq!(event.alarms.len(), 2);
+        assert_eq!(
+            value(&event.alarms[1].properties, "TRIGGER"),
+            Some("-PT1440M")
+        );
+    }
+
+    #[test]
+    fn todo_without_deadline_becomes_vtodo() {
+        let calendar = parse(&todo_list_to_ics(
+            &vec![todo(2, "阅读材料", None)],
+            &vec![60],
+            &HashMap::new(),
+        ));
+        assert!(calendar.events.is_empty());
+        assert_eq!(calendar.todos.len(), 1);
+        let todo = &calendar.todos[0];
+        assert_eq!(
+            value(&todo.properties, "UID"),
+            Some("todo-100-2@courses.zju.edu.cn")
+        );
+        assert_eq!(value(&todo.properties, "STATUS"), Some("NEEDS-ACTION"));
+        assert!(value(&todo.properties, "DTSTART").is_none());
+        assert!(todo.alarms.is_empty());
+    }
+}
@@ -1,6 +1,42 @@
+pub mod caldav;
 pub mod common;
+pub mod credit_audit;
+pub mod digest;
+pub mod email;
+pub mod evaluation;
+pub mod exam;
+pub mod forum;
+pub mod gpa;
+pub mod ics;
+pub mod notifier;
+pub mod pdf;
+pub mod quiz;
+pub mod score;
+pub mod score_analytics;
+pub mod score_export;
+pub mod seat;
+pub mod timetable;
+pub mod todo_export;
 
+pub use caldav::*;
 pub use common::*;
+pub use credit_audit::*;
+pub use digest::*;
+pub use email::*;
+pub use evaluation::*;
+pub use exam::*;
+pub use forum::*;
+pub use gpa::*;
+pub use ics::*;
+pub use notifier::*;
+pub use pdf::*;
+pub use quiz::*;
+pub use score::*;
+pub use score_analytics::*;
+pub use score_export::*;
+pub use seat::*;
+pub use timetable::*;
+pub use todo_export::*;
 
 #[cfg(target_os = "macos")]
 pub mod macos;
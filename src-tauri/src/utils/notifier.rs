@@ -0,0 +1,401 @@
+use crate::model::{Config, NotifyChannel, NotifyEvent};
+use crate::utils::send_email;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use log::info;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::time::Duration;
+
+const SEND_ATTEMPTS: u32 = 3;
+
+// title and a markdown body, channels without markdown get plain text
+pub trait Notifier {
+    fn build(
+        &self,
+        client: &Client,
+        channel: &NotifyChannel,
+        title: &str,
+        text: &str,
+    ) -> RequestBuilder;
+
+    // some services answer 200 and put the error in the body
+    fn check(&self, _res: &Value) -> Result<(), String> {
+        Ok(())
+    }
+
+    // errors in the body worth another attempt, like rate limits
+    fn is_transient(&self, _res: &Value) -> bool {
+        false
+    }
+}
+
+pub fn notifier(kind: &str) -> Option<Box<dyn Notifier + Send + Sync>> {
+    match kind {
+        "ding" => Some(Box::new(DingNotifier)),
+        "feishu" => Some(Box::new(FeishuNotifier)),
+        "wecom" => Some(Box::new(WeComNotifier)),
+        "telegram" => Some(Box::new(TelegramNotifier)),
+        "bark" => Some(Box::new(BarkNotifier)),
+        "serverchan" => Some(Box::new(ServerChanNotifier)),
+        "ntfy" => Some(Box::new(NtfyNotifier)),
+        "gotify" => Some(Box::new(GotifyNotifier)),
+        _ => None,
+    }
+}
+
+pub fn plain_text(text: &str) -> String {
+    text.lines()
+        .map(|line| line.trim_start_matches('#').trim_start().replace("**", ""))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn server_url(channel: &NotifyChannel, default: &str) -> String {
+    let url = if channel.url.is_empty() {
+        default
+    } else {
+        &channel.url
+    };
+    url.trim_end_matches('/').to_string()
+}
+
+fn check_code(res: &Value, key: &str, ok: i64, message_key: &str) -> Result<(), String> {
+    match res[key].as_i64() {
+        Some(code) if code != ok => Err(format!(
+            "{} ({})",
+            res[message_key].as_str().unwrap_or("发送失败"),
+            code
+        )),
+        _ => Ok(()),
+    }
+}
+
+// reference:
+// https://open.dingtalk.com/document/robots/customize-robot-security-settings
+pub fn ding_sign(secret: &str, timestamp: i64) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(format!("{}\n{}", timestamp, secret).as_bytes());
+    STANDARD.encode(mac.finalize().into_bytes())
+}
+
+// token is the signing secret, empty for the keyword mode
+pub struct DingNotifier;
+
+impl Notifier for DingNotifier {
+    fn build(
+        &self,
+        client: &Client,
+        channel: &NotifyChannel,
+        title: &str,
+        text: &str,
+    ) -> RequestBuilder {
+        let mut builder = client.post(&channel.url);
+        if !channel.token.is_empty() {
+            // signed per request, the server rejects a timestamp older than an hour
+            let timestamp = Utc::now().timestamp_millis();
+            builder = builder.query(&[
+                ("timestamp", timestamp.to_string()),
+                ("sign", ding_sign(&channel.token, timestamp)),
+            ]);
+        }
+        builder.json(&json!({
+            "msgtype": "markdown",
+            "markdown": {
+                "title": title,
+                "text": format!("### {}\n{}", title, text)
+            }
+        }))
+    }
+
+    fn check(&self, res: &Value) -> Result<(), String> {
+        check_code(res, "errcode", 0, "errmsg")
+    }
+
+    // -1 is system busy and 130101 is sending too fast
+    fn is_transient(&self, res: &Value) -> bool {
+        matches!(res["errcode"].as_i64(), Some(-1) | Some(130101))
+    }
+}
+
+// reference:
+// https://open.feishu.cn/document/client-docs/bot-v3/add-custom-bot
+pub struct FeishuNotifier;
+
+impl Notifier for FeishuNotifier {
+    fn build(
+        &self,
+        client: &Client,
+        channel: &NotifyChannel,
+        title: &str,
+        text: &str,
+    ) -> RequestBuilder {
+        client.post(&channel.url).json(&json!({
+            "msg_type": "interactive",
+            "card": {
+                "header": {
+                    "title": { "tag": "plain_text", "content": title }
+                },
+                "elements": [{ "tag": "markdown", "content": text }]
+            }
+        }))
+    }
+
+    fn check(&self, res: &Value) -> Result<(), String> {
+        check_code(res, "code", 0, "msg")
+    }
+}
+
+pub struct WeComNotifier;
+
+impl Notifier for WeComNotifier {
+    fn build(
+        &self,
+        client: &Client,
+        channel: &NotifyChannel,
+        title: &str,
+        text: &str,
+    ) -> RequestBuilder {
+        client.post(&channel.url).json(&json!({
+            "msgtype": "markdown",
+            "markdown": {
+                "content": format!("### {}\n{}", title, text)
+            }
+        }))
+    }
+
+    fn check(&self, res: &Value) -> Result<(), String> {
+        check_code(res, "errcode", 0, "errmsg")
+    }
+}
+
+// url is only needed behind a bot api proxy
+pub struct TelegramNotifier;
+
+impl Notifier for TelegramNotifier {
+    fn build(
+        &self,
+        client: &Client,
+        channel: &NotifyChannel,
+        title: &str,
+        text: &str,
+    ) -> RequestBuilder {
+        let url = format!(
+            "{}/bot{}/sendMessage",
+            server_url(channel, "https://api.telegram.org"),
+            channel.token
+        );
+        client.post(url).json(&json!({
+            "chat_id": channel.target,
+            "text": format!("{}\n{}", title, plain_text(text)),
+        }))
+    }
+
+    fn check(&self, res: &Value) -> Result<(), String> {
+        match res["ok"].as_bool() {
+            Some(false) => Err(res["description"]
+                .as_str()
+                .unwrap_or("发送失败")
+                .to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub struct BarkNotifier;
+
+impl Notifier for BarkNotifier {
+    fn build(
+        &self,
+        client: &Client,
+        channel: &NotifyChannel,
+        title: &str,
+        text: &str,
+    ) -> RequestBuilder {
+        let url = format!(
+            "{}/{}",
+            server_url(channel, "https://api.day.app"),
+            channel.token
+        );
+        client.post(url).json(&json!({
+            "title": title,
+            "body": plain_text(text),
+            "group": "ZJU Learning Assistant",
+        }))
+    }
+
+    fn check(&self, res: &Value) -> Result<(), String> {
+        check_code(res, "code", 200, "message")
+    }
+}
+
+pub struct ServerChanNotifier;
+
+impl Notifier for ServerChanNotifier {
+    fn build(
+        &self,
+        client: &Client,
+        channel: &NotifyChannel,
+        title: &str,
+        text: &str,
+    ) -> RequestBuilder {
+        let url = format!(
+            "{}/{}.send",
+            server_url(channel, "https://sctapi.ftqq.com"),
+            channel.token
+        );
+        client.post(url).form(&[("title", title), ("desp", text)])
+    }
+
+    fn check(&self, res: &Value) -> Result<(), String> {
+        check_code(res, "code", 0, "message")
+    }
+}
+
+// reference:
+// https://docs.ntfy.sh/publish/#publish-as-json
+pub struct NtfyNotifier;
+
+impl Notifier for NtfyNotifier {
+    fn build(
+        &self,
+        client: &Client,
+        channel: &NotifyChannel,
+        title: &str,
+        text: &str,
+    ) -> RequestBuilder {
+        let builder = client
+            .post(server_url(channel, "https://ntfy.sh"))
+            .json(&json!({
+                "topic": channel.target,
+                "title": title,
+                "message": text,
+                "markdown": true,
+            }));
+        // access token of a protected topic
+        if channel.token.is_empty() {
+            builder
+        } else {
+            builder.bearer_auth(&channel.token)
+        }
+    }
+}
+
+pub struct GotifyNotifier;
+
+impl Notifier for GotifyNotifier {
+    fn build(
+        &self,
+        client: &Client,
+        channel: &NotifyChannel,
+        title: &str,
+        text: &str,
+    ) -> RequestBuilder {
+        let url = format!("{}/message", server_url(channel, ""));
+        client
+            .post(url)
+            .header("X-Gotify-Key", &channel.token)
+            .json(&json!({
+                "title": title,
+                "message": text,
+                "priority": 5,
+                "extras": {
+                    "client::display": { "contentType": "text/markdown" }
+                }
+            }))
+    }
+}
+
+pub async fn send_notification(
+    channel: &NotifyChannel,
+    title: &str,
+    text: &str,
+) -> Result<(), String> {
+    info!("send_notification: {} {}", channel.kind, title);
+    let notifier = notifier(&channel.kind).ok_or(format!("不支持的通知渠道：{}", channel.kind))?;
+    let client = Client::new();
+    let mut attempt = 1;
+    loop {
+        let res = send_once(&*notifier, &client, channel, title, text).await;
+        match res {
+            Err((err, true)) if attempt < SEND_ATTEMPTS => {
+                info!("send_notification: attempt {} failed {}", attempt, err);
+                tokio::time::sleep(Duration::from_secs(2u64.pow(attempt))).await;
+                attempt += 1;
+            }
+            res => return res.map_err(|(err, _)| format!("{} 报错: {}", channel.name, err)),
+        }
+    }
+}
+
+// the error tells whether it is worth retrying
+async fn send_once(
+    notifier: &(dyn Notifier + Send + Sync),
+    client: &Client,
+    channel: &NotifyChannel,
+    title: &str,
+    text: &str,
+) -> Result<(), (String, bool)> {
+    let res = notifier
+        .build(client, channel, title, text)
+        .send()
+        .await
+        .map_err(|err| (err.to_string(), err.is_timeout() || err.is_connect()))?;
+    let status = res.status();
+    let body = res.text().await.map_err(|err| (err.to_string(), true))?;
+    if !status.is_success() {
+        let transient = status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS;
+        return Err((format!("({}) {}", status, body), transient));
+    }
+    let body = serde_json::from_str::<Value>(&body).unwrap_or(Value::Null);
+    notifier
+        .check(&body)
+        .map_err(|err| (err, notifier.is_transient(&body)))
+}
+
+// ding_url predates the channels and only ever got score messages, robots set
+// up with a keyword would reject anything else
+pub fn routed_channels(config: &Config, event: NotifyEvent) -> Vec<NotifyChannel> {
+    let mut channels = config
+        .notify_channels
+        .iter()
+        .filter(|channel| channel.enabled && channel.events.contains(&event))
+        .cloned()
+        .collect::<Vec<_>>();
+    if !config.ding_url.is_empty() && event == NotifyEvent::Score {
+        channels.push(ding_channel(&config.ding_url, &config.ding_secret));
+    }
+    channels
+}
+
+pub fn ding_channel(ding_url: &str, ding_secret: &str) -> NotifyChannel {
+    NotifyChannel {
+        name: "钉钉机器人".to_string(),
+        kind: "ding".to_string(),
+        url: ding_url.to_string(),
+        token: ding_secret.to_string(),
+        target: "".to_string(),
+        enabled: true,
+        events: vec![],
+    }
+}
+
+pub fn routed_email(config: &Config, event: NotifyEvent) -> bool {
+    config.smtp_enabled && config.smtp_events.contains(&event)
+}
+
+// failures are only logged, one broken channel should not block the others
+pub async fn dispatch_notification(config: &Config, event: NotifyEvent, title: &str, text: &str) {
+    for channel in routed_channels(config, event).iter() {
+        if let Err(err) = send_notification(channel, title, text).await {
+            info!("dispatch_notification: {}", err);
+        }
+    }
+    if routed_email(config, event) {
+        if let Err(err) = send_email(config, title, &plain_text(text)).await {
+            info!("dispatch_notification: {}", err);
+        }
+    }
+}
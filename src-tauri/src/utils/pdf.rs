@@ -0,0 +1,471 @@
+use log::info;
+use miniz_oxide::deflate::{compress_to_vec_zlib, CompressionLevel};
+use pdf_writer::types::{CidFontType, FontFlags, SystemInfo, UnicodeCmap};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use subsetter::{subset, Profile};
+use ttf_parser::{name_id, Face, GlyphId, Permissions};
+
+// A4 in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+const LINE_SPACING: f32 = 1.5;
+
+const FONT_NAME: Name = Name(b"F1");
+// only used when no embeddable cjk font is found: names the Adobe-GB1 standard
+// font, the reader substitutes a local one. text is written as UCS-2 through
+// UniGB-UCS2-H, characters outside the BMP cannot be shown
+const FALLBACK_FONT: Name = Name(b"STSong-Light");
+
+// looked up after the fonts bundled in the app resources
+const SYSTEM_FONTS: &[&str] = &[
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\simsun.ttc",
+    "/System/Library/Fonts/STHeiti Light.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc",
+];
+
+static FONT_DIR: OnceLock<PathBuf> = OnceLock::new();
+static CJK_FONT: OnceLock<Option<(Vec<u8>, u32)>> = OnceLock::new();
+
+// called once at startup with the bundled font directory
+pub fn set_font_dir(dir: PathBuf) {
+    let _ = FONT_DIR.set(dir);
+}
+
+struct CjkFont {
+    data: &'static [u8],
+    index: u32,
+    face: Face<'static>,
+}
+
+fn embeddable(face: &Face) -> bool {
+    // cff2 outlines cannot be embedded in a pdf
+    !matches!(face.permissions(), Some(Permissions::Restricted))
+        && face.is_subsetting_allowed()
+        && face.tables().cff2.is_none()
+        && face.glyph_index('中').is_some()
+}
+
+fn load_font(path: &Path) -> Option<(Vec<u8>, u32)> {
+    let data = std::fs::read(path).ok()?;
+    let count = ttf_parser::fonts_in_collection(&data).unwrap_or(1);
+    let index =
+        (0..count).find(|index| Face::parse(&data, *index).is_ok_and(|face| embeddable(&face)))?;
+    Some((data, index))
+}
+
+fn font_candidates() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Some(Ok(entries)) = FONT_DIR.get().map(std::fs::read_dir) {
+        let mut bundled: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+                ["ttf", "otf", "ttc", "otc"].contains(&ext.to_lowercase().as_str())
+            })
+            .collect();
+        bundled.sort();
+        paths.extend(bundled);
+    }
+    paths.extend(SYSTEM_FONTS.iter().map(PathBuf::from));
+    paths
+}
+
+fn cjk_font() -> Option<CjkFont> {
+    let (data, index) = CJK_FONT
+        .get_or_init(|| {
+            let found = font_candidates()
+                .into_iter()
+                .find_map(|path| load_font(&path).map(|font| (path, font)));
+            match found {
+                Some((path, font)) => {
+                    info!("cjk_font: embedding {}", path.display());
+                    Some(font)
+                }
+                None => {
+                    info!("cjk_font: no embeddable font found, falling back to STSong-Light");
+                    None
+                }
+            }
+        })
+        .as_ref()?;
+    let face = Face::parse(data, *index).ok()?;
+    Some(CjkFont {
+        data,
+        index: *index,
+        face,
+    })
+}
+
+// characters missing from the font are drawn as .notdef
+fn glyph_id(face: &Face, c: char) -> GlyphId {
+    face.glyph_index(c).unwrap_or(GlyphId(0))
+}
+
+// glyph advance in thousandths of the font size
+fn advance(face: &Face, glyph: GlyphId) -> f32 {
+    let advance = face.glyph_hor_advance(glyph).unwrap_or(0);
+    advance as f32 * 1000.0 / face.units_per_em() as f32
+}
+
+fn char_width(face: Option<&Face>, c: char, size: f32) -> f32 {
+    match face {
+        Some(face) => advance(face, glyph_id(face, c)) * size / 1000.0,
+        None if c.is_ascii() => size * 0.5,
+        None => size,
+    }
+}
+
+pub fn text_width(text: &str, size: f32) -> f32 {
+    let font = cjk_font();
+    let face = font.as_ref().map(|font| &font.face);
+    text.chars().map(|c| char_width(face, c, size)).sum()
+}
+
+fn encode_ucs2(text: &str) -> Vec<u8> {
+    text.chars()
+        .flat_map(|c| {
+            // characters outside the BMP cannot be shown with a UCS-2 cmap
+            let code = if (c as u32) > 0xFFFF { '?' as u16 } else { c as u16 };
+            code.to_be_bytes()
+        })
+        .collect()
+}
+
+// six letter subset tag, derived from the glyph set so the same text gives the same pdf
+fn subset_tag(glyphs: &BTreeMap<u16, char>) -> String {
+    let mut hash = glyphs.keys().fold(17u32, |hash, glyph| {
+        hash.wrapping_mul(31).wrapping_add(*glyph as u32)
+    });
+    (0..6)
+        .map(|_| {
+            let letter = (b'A' + (hash % 26) as u8) as char;
+            hash /= 26;
+            letter
+        })
+        .collect()
+}
+
+fn postscript_name(face: &Face) -> String {
+    let name = face
+        .names()
+        .into_iter()
+        .find(|name| name.name_id == name_id::POST_SCRIPT_NAME && name.is_unicode())
+        .and_then(|name| name.to_string())
+        .unwrap_or_default();
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    if name.is_empty() {
+        "CJKFont".to_string()
+    } else {
+        name
+    }
+}
+
+fn wrap_line(face: Option<&Face>, line: &str, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut width = 0.0;
+    for c in line.chars() {
+        let w = char_width(face, c, size);
+        if width + w > max_width && !current.is_empty() {
+            lines.push(current);
+            current = String::new();
+            width = 0.0;
+        }
+        current.push(c);
+        width += w;
+    }
+    lines.push(current);
+    lines
+}
+
+// a minimal flowing text document, used for quiz and transcript exports
+pub struct TextPdf {
+    pages: Vec<Vec<u8>>,
+    content: Content,
+    y: f32,
+    font: Option<CjkFont>,
+    glyphs: BTreeMap<u16, char>, // glyph id -> character, for the ToUnicode cmap
+}
+
+impl TextPdf {
+    pub fn new() -> Self {
+        Self {
+            pages: Vec::new(),
+            content: Content::new(),
+            y: PAGE_HEIGHT - MARGIN,
+            font: cjk_font(),
+            glyphs: BTreeMap::new(),
+        }
+    }
+
+    pub fn content_width(&self) -> f32 {
+        PAGE_WIDTH - MARGIN * 2.0
+    }
+
+    fn new_page(&mut self) {
+        let content = std::mem::replace(&mut self.content, Content::new());
+        self.pages.push(content.finish());
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    fn advance(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+        self.y -= height;
+    }
+
+    // glyph ids through Identity-H with the embedded font, UCS-2 otherwise
+    fn encode(&mut self, text: &str) -> Vec<u8> {
+        let face = match &self.font {
+            Some(font) => &font.face,
+            None => return encode_ucs2(text),
+        };
+        let mut encoded = Vec::new();
+        for c in text.chars() {
+            let glyph = glyph_id(face, c);
+            self.glyphs.entry(glyph.0).or_insert(c);
+            encoded.extend(glyph.0.to_be_bytes());
+        }
+        encoded
+    }
+
+    fn show(&mut self, x: f32, size: f32, text: &str) {
+        let encoded = self.encode(text);
+        self.content.begin_text();
+        self.content.set_font(FONT_NAME, size);
+        self.content.next_line(MARGIN + x, self.y);
+        self.content.show(Str(&encoded));
+        self.content.end_text();
+    }
+
+    pub fn paragraph(&mut self, text: &str, size: f32) {
+        let max_width = self.content_width();
+        let face = self.font.as_ref().map(|font| font.face.clone());
+        for line in text.lines() {
+            for wrapped in wrap_line(face.as_ref(), line, size, max_width) {
+                self.advance(size * LINE_SPACING);
+                self.show(0.0, size, &wrapped);
+            }
+        }
+    }
+
+    // a single line with cells at x offsets relative to the left margin
+    pub fn row(&mut self, cells: &[(f32, &str)], size: f32) {
+        self.advance(size * LINE_SPACING);
+        for (x, text) in cells {
+            self.show(*x, size, text);
+        }
+    }
+
+    pub fn rule(&mut self) {
+        self.advance(6.0);
+        self.content.set_line_width(0.5);
+        self.content.move_to(MARGIN, self.y);
+        self.content.line_to(PAGE_WIDTH - MARGIN, self.y);
+        self.content.stroke();
+    }
+
+    pub fn space(&mut self, height: f32) {
+        self.advance(height);
+    }
+
+    pub fn page_break(&mut self) {
+        self.new_page();
+    }
+
+    pub fn save(self, pdf_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(pdf_path, self.finish())?;
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.new_page();
+
+        let mut pdf = Pdf::new();
+        let catalog_id = Ref::new(1);
+        let page_tree_id = Ref::new(2);
+        let font_ids = FontRefs {
+            font: Ref::new(3),
+            cid_font: Ref::new(4),
+            descriptor: Ref::new(5),
+            to_unicode: Ref::new(6),
+            font_file: Ref::new(7),
+        };
+        match &self.font {
+            Some(font) => write_embedded_font(&mut pdf, &font_ids, font, &self.glyphs),
+            None => write_fallback_font(&mut pdf, &font_ids),
+        }
+
+        let mut page_ids = Vec::new();
+        for (index, content) in self.pages.iter().enumerate() {
+            let page_id = Ref::new(index as i32 * 2 + 8);
+            let content_id = Ref::new(index as i32 * 2 + 9);
+            page_ids.push(page_id);
+
+            let mut page = pdf.page(page_id);
+            page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+            page.parent(page_tree_id);
+            page.contents(content_id);
+            page.resources().fonts().pair(FONT_NAME, font_ids.font);
+            page.finish();
+
+            pdf.stream(content_id, content);
+        }
+
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        let page_num = page_ids.len() as i32;
+        pdf.pages(page_tree_id).kids(page_ids).count(page_num);
+
+        pdf.finish()
+    }
+}
+
+struct FontRefs {
+    font: Ref,
+    cid_font: Ref,
+    descriptor: Ref,
+    to_unicode: Ref,
+    font_file: Ref,
+}
+
+fn write_embedded_font(
+    pdf: &mut Pdf,
+    ids: &FontRefs,
+    font: &CjkFont,
+    glyphs: &BTreeMap<u16, char>,
+) {
+    let face = &font.face;
+    let is_cff = face.tables().cff.is_some();
+    let base_font = format!("{}+{}", subset_tag(glyphs), postscript_name(face));
+    let base_font = Name(base_font.as_bytes());
+    let system_info = SystemInfo {
+        registry: Str(b"Adobe"),
+        ordering: Str(b"Identity"),
+        supplement: 0,
+    };
+
+    pdf.type0_font(ids.font)
+        .base_font(base_font)
+        .encoding_predefined(Name(b"Identity-H"))
+        .descendant_font(ids.cid_font)
+        .to_unicode(ids.to_unicode);
+
+    let subtype = if is_cff {
+        CidFontType::Type0
+    } else {
+        CidFontType::Type2
+    };
+    let mut cid_font = pdf.cid_font(ids.cid_font);
+    cid_font
+        .subtype(subtype)
+        .base_font(base_font)
+        .system_info(system_info)
+        .font_descriptor(ids.descriptor)
+        .default_width(advance(face, GlyphId(0)));
+    if !is_cff {
+        cid_font.cid_to_gid_map_predefined(Name(b"Identity"));
+    }
+    let mut widths = cid_font.widths();
+    for glyph in glyphs.keys() {
+        widths.consecutive(*glyph, [advance(face, GlyphId(*glyph))]);
+    }
+    widths.finish();
+    cid_font.finish();
+
+    let mut cmap = UnicodeCmap::new(Name(b"Custom"), system_info);
+    for (glyph, c) in glyphs {
+        cmap.pair(*glyph, *c);
+    }
+    pdf.cmap(ids.to_unicode, &cmap.finish());
+
+    let scale = |value: i16| value as f32 * 1000.0 / face.units_per_em() as f32;
+    let bbox = face.global_bounding_box();
+    let mut descriptor = pdf.font_descriptor(ids.descriptor);
+    descriptor
+        .name(base_font)
+        .flags(FontFlags::SYMBOLIC)
+        .bbox(Rect::new(
+            scale(bbox.x_min),
+            scale(bbox.y_min),
+            scale(bbox.x_max),
+            scale(bbox.y_max),
+        ))
+        .italic_angle(0.0)
+        .ascent(scale(face.ascender()))
+        .descent(scale(face.descender()))
+        .cap_height(scale(face.capital_height().unwrap_or(face.ascender())))
+        .stem_v(80.0);
+
+    // the subset keeps the original glyph ids, so Identity-H still applies
+    let mut subset_glyphs: Vec<u16> = glyphs.keys().copied().collect();
+    subset_glyphs.push(0);
+    let subsetted = match subset(font.data, font.index, Profile::pdf(&subset_glyphs)) {
+        Ok(subsetted) => subsetted,
+        Err(err) => {
+            info!("write_embedded_font: failed to subset the font: {}", err);
+            return;
+        }
+    };
+    if is_cff {
+        descriptor.font_file3(ids.font_file);
+    } else {
+        descriptor.font_file2(ids.font_file);
+    }
+    descriptor.finish();
+
+    let level = CompressionLevel::DefaultLevel as u8;
+    let compressed = compress_to_vec_zlib(&subsetted, level);
+    let mut stream = pdf.stream(ids.font_file, &compressed);
+    stream.filter(Filter::FlateDecode);
+    if is_cff {
+        stream.pair(Name(b"Subtype"), Name(b"OpenType"));
+    }
+    stream.finish();
+}
+
+fn write_fallback_font(pdf: &mut Pdf, ids: &FontRefs) {
+    pdf.type0_font(ids.font)
+        .base_font(FALLBACK_FONT)
+        .encoding_predefined(Name(b"UniGB-UCS2-H"))
+        .descendant_font(ids.cid_font);
+    let mut cid_font = pdf.cid_font(ids.cid_font);
+    cid_font
+        .subtype(CidFontType::Type0)
+        .base_font(FALLBACK_FONT)
+        .system_info(SystemInfo {
+            registry: Str(b"Adobe"),
+            ordering: Str(b"GB1"),
+            supplement: 2,
+        })
+        .font_descriptor(ids.descriptor)
+        .default_width(1000.0);
+    // latin glyphs are half width
+    cid_font.widths().same(1, 95, 500.0).same(814, 907, 500.0);
+    cid_font.finish();
+    pdf.font_descriptor(ids.descriptor)
+        .name(FALLBACK_FONT)
+        .flags(FontFlags::SYMBOLIC)
+        .bbox(Rect::new(-25.0, -254.0, 1000.0, 880.0))
+        .italic_angle(0.0)
+        .ascent(880.0)
+        .descent(-120.0)
+        .cap_height(880.0)
+        .stem_v(93.0);
+}
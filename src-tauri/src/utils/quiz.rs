@@ -0,0 +1,129 @@
+use crate::model::QuizActivity;
+use crate::utils::{html_to_text, TextPdf};
+use chrono::{DateTime, Local, Utc};
+
+fn format_time(time: &Option<String>) -> String {
+    match time {
+        Some(time) => match time.parse::<DateTime<Utc>>() {
+            Ok(time) => time
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            Err(_) => time.clone(),
+        },
+        None => "-".to_string(),
+    }
+}
+
+fn format_score(score: Option<f64>) -> String {
+    match score {
+        Some(score) => format!("{}", score),
+        None => "-".to_string(),
+    }
+}
+
+fn option_label(index: usize) -> char {
+    (b'A' + index as u8) as char
+}
+
+pub fn quiz_to_markdown(quiz: &QuizActivity) -> String {
+    let mut md = String::new();
+    md.push_str(&format!("# {}\n\n", quiz.title));
+    md.push_str(&format!(
+        "- **开始时间**：{}\n- **截止时间**：{}\n- **总分**：{}\n\n",
+        format_time(&quiz.start_time),
+        format_time(&quiz.end_time),
+        format_score(quiz.total_points)
+    ));
+    if quiz.attempts.is_empty() {
+        md.push_str("未作答\n");
+    }
+
+    for (index, attempt) in quiz.attempts.iter().enumerate() {
+        md.push_str(&format!(
+            "## 第 {} 次作答（{}，得分 {}）\n\n",
+            index + 1,
+            format_time(&attempt.submitted_at),
+            format_score(attempt.score)
+        ));
+        for (number, question) in attempt.questions.iter().enumerate() {
+            md.push_str(&format!(
+                "### {}. {}（{} 分）\n\n",
+                number + 1,
+                html_to_text(&question.description),
+                format_score(question.point)
+            ));
+            for (i, option) in question.options.iter().enumerate() {
+                md.push_str(&format!("- {}. {}\n", option_label(i), html_to_text(option)));
+            }
+            if !question.options.is_empty() {
+                md.push_str("\n");
+            }
+            md.push_str(&format!("**我的答案**：{}\n\n", html_to_text(&question.answer)));
+            if let Some(correct_answer) = &question.correct_answer {
+                md.push_str(&format!("**参考答案**：{}\n\n", html_to_text(correct_answer)));
+            }
+            if question.score.is_some() {
+                md.push_str(&format!("**得分**：{}\n\n", format_score(question.score)));
+            }
+        }
+    }
+    md
+}
+
+pub fn quiz_to_pdf(quiz: &QuizActivity, pdf_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut pdf = TextPdf::new();
+    pdf.paragraph(&quiz.title, 18.0);
+    pdf.space(6.0);
+    pdf.paragraph(
+        &format!(
+            "开始时间：{}    截止时间：{}    总分：{}",
+            format_time(&quiz.start_time),
+            format_time(&quiz.end_time),
+            format_score(quiz.total_points)
+        ),
+        10.0,
+    );
+    pdf.rule();
+    if quiz.attempts.is_empty() {
+        pdf.paragraph("未作答", 11.0);
+    }
+
+    for (index, attempt) in quiz.attempts.iter().enumerate() {
+        pdf.space(6.0);
+        pdf.paragraph(
+            &format!(
+                "第 {} 次作答（{}，得分 {}）",
+                index + 1,
+                format_time(&attempt.submitted_at),
+                format_score(attempt.score)
+            ),
+            14.0,
+        );
+        for (number, question) in attempt.questions.iter().enumerate() {
+            pdf.space(4.0);
+            pdf.paragraph(
+                &format!(
+                    "{}. {}（{} 分）",
+                    number + 1,
+                    html_to_text(&question.description),
+                    format_score(question.point)
+                ),
+                11.0,
+            );
+            for (i, option) in question.options.iter().enumerate() {
+                pdf.paragraph(&format!("    {}. {}", option_label(i), html_to_text(option)), 10.0);
+            }
+            pdf.paragraph(&format!("我的答案：{}", html_to_text(&question.answer)), 10.0);
+            if let Some(correct_answer) = &question.correct_answer {
+                pdf.paragraph(&format!("参考答案：{}", html_to_text(correct_answer)), 10.0);
+            }
+            if question.score.is_some() {
+                pdf.paragraph(&format!("得分：{}", format_score(question.score)), 10.0);
+            }
+        }
+        pdf.rule();
+    }
+
+    pdf.save(pdf_path)
+}
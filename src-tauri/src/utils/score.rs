@@ -0,0 +1,247 @@
+use crate::model::ScoreRecord;
+use crate::utils::json_text;
+use serde_json::Value;
+use std::collections::HashMap;
+
+// records with these scores have no grade point and are left out of the GPA
+const NON_GRADED_SCORES: [&str; 3] = ["合格", "不合格", "弃修"];
+
+// a passed makeup exam is recorded as 60 on the 5.0 scale
+const MAKEUP_PASS_GRADE_POINT: f64 = 1.5;
+
+// xkkh looks like "(2023-2024-1)-211G0200-0001"
+fn split_xkkh(xkkh: &str) -> (String, String) {
+    let semester = xkkh
+        .strip_prefix('(')
+        .and_then(|rest| rest.split(')').next())
+        .unwrap_or("")
+        .to_string();
+    let course_code = xkkh
+        .split(')')
+        .nth(1)
+        .and_then(|rest| rest.trim_start_matches('-').split('-').next())
+        .unwrap_or(xkkh)
+        .to_string();
+    (semester, course_code)
+}
+
+pub fn is_graded_score(score: &str) -> bool {
+    !NON_GRADED_SCORES.contains(&score.trim())
+}
+
+fn is_passed(score: &str) -> bool {
+    match score.trim().parse::<f64>() {
+        Ok(score) => score >= 60.0,
+        Err(_) => matches!(
+            score.trim(),
+            "优秀" | "良好" | "中等" | "及格" | "合格" | "优" | "良" | "中"
+        ),
+    }
+}
+
+// passed either the first time or in the makeup exam
+pub fn is_record_passed(record: &ScoreRecord) -> bool {
+    is_passed(&record.score)
+        || record
+            .makeup_score
+            .as_deref()
+            .is_some_and(|makeup_score| is_passed(makeup_score))
+}
+
+pub fn parse_score_record(item: &Value) -> ScoreRecord {
+    let xkkh = json_text(item, &["xkkh"]);
+    let (semester, course_code) = split_xkkh(&xkkh);
+    let makeup_score = Some(json_text(item, &["bkcj"])).filter(|s| !s.is_empty());
+    let score = json_text(item, &["cj"]);
+    let grade_point = json_text(item, &["jd"]).parse::<f64>().unwrap_or(0.0);
+    ScoreRecord {
+        xkkh,
+        course_code,
+        semester,
+        course_name: json_text(item, &["kcmc"]),
+        counted: is_graded_score(&score),
+        score,
+        makeup_score,
+        credit: json_text(item, &["xf"]).parse::<f64>().unwrap_or(0.0),
+        grade_point,
+        effective_grade_point: grade_point,
+        makeup_replaced: false,
+    }
+}
+
+// official precedence: a passed makeup replaces a failed score (as 60), and
+// for a course taken more than once only the best attempt counts
+pub fn apply_score_rules(records: &mut Vec<ScoreRecord>) {
+    for record in records.iter_mut() {
+        if let Some(makeup_score) = &record.makeup_score {
+            if !is_passed(&record.score) && is_passed(makeup_score) {
+                record.effective_grade_point = record.grade_point.max(MAKEUP_PASS_GRADE_POINT);
+                record.makeup_replaced = true;
+            }
+        }
+    }
+
+    let mut best: HashMap<String, usize> = HashMap::new();
+    for (index, record) in records.iter().enumerate() {
+        if !record.counted || record.course_code.is_empty() {
+            continue;
+        }
+        match best.get(&record.course_code) {
+            Some(&other)
+                if records[other].effective_grade_point > record.effective_grade_point
+                    || (records[other].effective_grade_point == record.effective_grade_point
+                        && records[other].semester >= record.semester) => {}
+            _ => {
+                best.insert(record.course_code.clone(), index);
+            }
+        }
+    }
+    for (index, record) in records.iter_mut().enumerate() {
+        if record.counted && !record.course_code.is_empty() {
+            record.counted = best.get(&record.course_code) == Some(&index);
+        }
+    }
+}
+
+pub fn parse_score_records(items: &Vec<Value>) -> Vec<ScoreRecord> {
+    let mut records = items.iter().map(parse_score_record).collect();
+    apply_score_rules(&mut records);
+    records
+}
+
+// add the outcome of the rules to the raw zdbk items, `jd` becomes the
+// effective grade point and the original one is kept in `original_jd`
+pub fn annotate_score_items(items: &mut Vec<Value>) {
+    let records = parse_score_records(items);
+    for (item, record) in items.iter_mut().zip(records.iter()) {
+        if record.makeup_replaced {
+            item["original_jd"] = item["jd"].clone();
+            item["jd"] = Value::String(format!("{:.1}", record.effective_grade_point));
+        }
+        item["makeup_replaced"] = Value::Bool(record.makeup_replaced);
+        item["counted"] = Value::Bool(record.counted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn item(xkkh: &str, cj: &str, bkcj: Option<&str>, jd: &str) -> Value {
+        json!({ "xkkh": xkkh, "kcmc": "程序设计", "cj": cj, "bkcj": bkcj, "xf": "3", "jd": jd })
+    }
+
+    fn records(items: &[Value]) -> Vec<ScoreRecord> {
+        parse_score_records(&items.to_vec())
+    }
+
+    #[test]
+    fn record_fields_are_parsed() {
+        let record = parse_score_record(&json!({
+            "xkkh": "(2023-2024-1)-211G0200-0001",
+            "kcmc": " 程序设计基础 ",
+            "cj": "90",
+            "xf": 3.5,
+            "jd": "4.5",
+        }));
+        assert_eq!(record.semester, "2023-2024-1");
+        assert_eq!(record.course_code, "211G0200");
+        assert_eq!(record.course_name, "程序设计基础");
+        assert_eq!(record.credit, 3.5);
+        assert_eq!(record.grade_point, 4.5);
+        assert_eq!(record.makeup_score, None);
+        assert!(record.counted);
+    }
+
+    #[test]
+    fn pass_fail_scores_are_not_counted() {
+        for score in ["合格", "不合格", "弃修"] {
+            assert!(!is_graded_score(score));
+            let records = records(&[item("(2023-2024-1)-211G0200-0001", score, None, "0")]);
+            assert!(!records[0].counted, "score {}", score);
+        }
+        assert!(is_graded_score("及格"));
+        assert!(is_graded_score("59"));
+    }
+
+    #[test]
+    fn passed_makeup_replaces_failed_score() {
+        let records = records(&[item("(2023-2024-1)-211G0200-0001", "45", Some("75"), "0")]);
+        assert!(records[0].makeup_replaced);
+        assert_eq!(records[0].grade_point, 0.0);
+        assert_eq!(records[0].effective_grade_point, MAKEUP_PASS_GRADE_POINT);
+        assert!(is_record_passed(&records[0]));
+    }
+
+    #[test]
+    fn failed_makeup_changes_nothing() {
+        let records = records(&[item("(2023-2024-1)-211G0200-0001", "45", Some("50"), "0")]);
+        assert!(!records[0].makeup_replaced);
+        assert_eq!(records[0].effective_grade_point, 0.0);
+        assert!(!is_record_passed(&records[0]));
+    }
+
+    #[test]
+    fn best_attempt_wins() {
+        let records = records(&[
+            item("(2022-2023-1)-211G0200-0001", "58", None, "0"),
+            item("(2022-2023-2)-211G0200-0002", "85", None, "3.9"),
+            item("(2023-2024-1)-211G0200-0003", "75", None, "2.7"),
+        ]);
+        let counted = records.iter().map(|r| r.counted).collect::<Vec<_>>();
+        assert_eq!(counted, [false, true, false]);
+    }
+
+    #[test]
+    fn makeup_is_compared_with_retakes() {
+        // the makeup 1.5 beats a later failed retake
+        let records = records(&[
+            item("(2022-2023-1)-211G0200-0001", "40", Some("65"), "0"),
+            item("(2022-2023-2)-211G0200-0002", "50", None, "0"),
+        ]);
+        let counted = records.iter().map(|r| r.counted).collect::<Vec<_>>();
+        assert_eq!(counted, [true, false]);
+    }
+
+    #[test]
+    fn latest_attempt_wins_a_tie() {
+        let first = item("(2022-2023-1)-211G0200-0001", "85", None, "3.9");
+        let second = item("(2022-2023-2)-211G0200-0002", "85", None, "3.9");
+        for items in [[first.clone(), second.clone()], [second, first]] {
+            let records = records(&items);
+            let counted = records
+                .iter()
+                .find(|record| record.counted)
+                .map(|record| record.semester.as_str());
+            assert_eq!(counted, Some("2022-2023-2"));
+            assert_eq!(records.iter().filter(|record| record.counted).count(), 1);
+        }
+    }
+
+    #[test]
+    fn pass_fail_record_does_not_supersede_graded_attempt() {
+        let records = records(&[
+            item("(2022-2023-1)-211G0200-0001", "70", None, "2.4"),
+            item("(2022-2023-2)-211G0200-0002", "合格", None, "0"),
+        ]);
+        let counted = records.iter().map(|r| r.counted).collect::<Vec<_>>();
+        assert_eq!(counted, [true, false]);
+    }
+
+    #[test]
+    fn items_are_annotated() {
+        let mut items = vec![
+            item("(2022-2023-1)-211G0200-0001", "40", Some("65"), "0"),
+            item("(2022-2023-1)-021B0020-0001", "合格", None, "0"),
+        ];
+        annotate_score_items(&mut items);
+        assert_eq!(items[0]["jd"], "1.5");
+        assert_eq!(items[0]["original_jd"], "0");
+        assert_eq!(items[0]["makeup_replaced"], true);
+        assert_eq!(items[0]["counted"], true);
+        assert_eq!(items[1]["jd"], "0");
+        assert!(items[1]["original_jd"].is_null());
+        assert_eq!(items[1]["counted"], false);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::model::{
+    CategoryStat, GpaOptions, GradeBucket, ScoreAnalytics, ScoreRecord, SemesterTrend,
+};
+use crate::utils::{csv_field, gpa_breakdown, gpa_summary, is_record_passed, percentage_score};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// lower bounds of the grade buckets, following the steps of the 5.0 scale
+const BUCKET_BOUNDS: [f64; 9] = [0.0, 60.0, 65.0, 70.0, 75.0, 80.0, 85.0, 90.0, 95.0];
+
+// courses missing from the last credit audit
+const UNCATEGORIZED: &str = "未分类";
+
+fn bucket_label(index: usize) -> String {
+    match index {
+        0 => "<60".to_string(),
+        i if i == BUCKET_BOUNDS.len() - 1 => format!("{}-100", BUCKET_BOUNDS[i]),
+        i => format!("{}-{}", BUCKET_BOUNDS[i], BUCKET_BOUNDS[i + 1] - 1.0),
+    }
+}
+
+fn semester_trend(records: &Vec<ScoreRecord>) -> Vec<SemesterTrend> {
+    let breakdown = gpa_breakdown(records, &GpaOptions::default());
+    // same records gpa_breakdown counts without options
+    let included = records
+        .iter()
+        .filter(|record| record.counted && record.credit > 0.0)
+        .collect::<Vec<_>>();
+
+    // a retaken course earns its credit in the semester it was first passed
+    let mut earned: BTreeMap<&str, f64> = BTreeMap::new();
+    let mut seen = HashSet::new();
+    let mut passed = records
+        .iter()
+        .filter(|record| is_record_passed(record))
+        .collect::<Vec<_>>();
+    passed.sort_by(|a, b| a.semester.cmp(&b.semester));
+    for record in passed {
+        if record.course_code.is_empty() || seen.insert(record.course_code.as_str()) {
+            *earned.entry(record.semester.as_str()).or_default() += record.credit;
+        }
+    }
+
+    let mut semesters = breakdown
+        .semesters
+        .iter()
+        .map(|semester| semester.semester.clone())
+        .chain(earned.keys().map(|semester| semester.to_string()))
+        .collect::<Vec<_>>();
+    semesters.sort();
+    semesters.dedup();
+
+    let mut cumulative_credit = 0.0;
+    semesters
+        .into_iter()
+        .map(|semester| {
+            let summary = breakdown
+                .semesters
+                .iter()
+                .find(|gpa| gpa.semester == semester)
+                .map(|gpa| gpa.summary.clone())
+                .unwrap_or_default();
+            let until = included
+                .iter()
+                .filter(|record| record.semester <= semester)
+                .cloned()
+                .collect::<Vec<_>>();
+            let earned_credit = earned.get(semester.as_str()).cloned().unwrap_or(0.0);
+            cumulative_credit += earned_credit;
+            SemesterTrend {
+                gpa: summary.gpa,
+                average_score: summary.average_score,
+                cumulative_gpa: gpa_summary(&until).gpa,
+                earned_credit,
+                cumulative_credit,
+                semester,
+            }
+        })
+        .collect()
+}
+
+fn grade_distribution(records: &Vec<ScoreRecord>) -> Vec<GradeBucket> {
+    let mut buckets = (0..BUCKET_BOUNDS.len())
+        .map(|index| GradeBucket {
+            label: bucket_label(index),
+            course_count: 0,
+            credit: 0.0,
+        })
+        .collect::<Vec<_>>();
+    for record in records.iter().filter(|record| record.counted) {
+        let score = match percentage_score(record) {
+            Some(score) => score,
+            None => continue,
+        };
+        let index = BUCKET_BOUNDS
+            .iter()
+            .rposition(|bound| score >= *bound)
+            .unwrap_or(0);
+        buckets[index].course_count += 1;
+        buckets[index].credit += record.credit;
+    }
+    buckets
+}
+
+fn category_stats(
+    records: &Vec<ScoreRecord>,
+    categories: &HashMap<String, String>,
+) -> Vec<CategoryStat> {
+    let mut grouped: BTreeMap<&str, Vec<&ScoreRecord>> = BTreeMap::new();
+    for record in records.iter().filter(|record| record.counted) {
+        let category = categories
+            .get(&record.course_code)
+            .map(|category| category.as_str())
+            .unwrap_or(UNCATEGORIZED);
+        grouped.entry(category).or_default().push(record);
+    }
+    grouped
+        .into_iter()
+        .map(|(category, records)| CategoryStat {
+            category: category.to_string(),
+            summary: gpa_summary(&records),
+        })
+        .filter(|stat| stat.summary.course_count > 0)
+        .collect()
+}
+
+// categories map course codes to training plan categories
+pub fn score_analytics(
+    records: &Vec<ScoreRecord>,
+    categories: &HashMap<String, String>,
+    synced_at: &str,
+) -> ScoreAnalytics {
+    ScoreAnalytics {
+        synced_at: synced_at.to_string(),
+        trend: semester_trend(records),
+        distribution: grade_distribution(records),
+        categories: category_stats(records, categories),
+    }
+}
+
+// one section per series, separated by an empty line
+pub fn analytics_to_csv(analytics: &ScoreAnalytics) -> String {
+    let mut csv = String::from("\u{feff}");
+    csv.push_str("学期,学期均绩,学期加权均分,累计均绩,获得学分,累计学分\r\n");
+    for trend in analytics.trend.iter() {
+        csv.push_str(&format!(
+            "{},{:.2},{:.2},{:.2},{},{}\r\n",
+            csv_field(&trend.semester),
+            trend.gpa,
+            trend.average_score,
+            trend.cumulative_gpa,
+            trend.earned_credit,
+            trend.cumulative_credit
+        ));
+    }
+    csv.push_str("\r\n分数段,课程数,学分\r\n");
+    for bucket in analytics.distribution.iter() {
+        csv.push_str(&format!(
+            "{},{},{}\r\n",
+            csv_field(&bucket.label),
+            bucket.course_count,
+            bucket.credit
+        ));
+    }
+    csv.push_str("\r\n类别,课程数,学分,均绩,加权均分\r\n");
+    for stat in analytics.categories.iter() {
+        csv.push_str(&format!(
+            "{},{},{},{:.2},{:.2}\r\n",
+            csv_field(&stat.category),
+            stat.summary.course_count,
+            stat.summary.credit,
+            stat.summary.gpa,
+            stat.summary.average_score
+        ));
+    }
+    csv
+}
@@ -0,0 +1,233 @@
+use crate::model::{GpaOptions, GpaSummary, ScoreRecord};
+use crate::utils::{gpa_breakdown, text_width, TextPdf};
+use chrono::Local;
+use rust_xlsxwriter::{Format, Workbook};
+use std::collections::BTreeMap;
+
+// column keys accepted by the exporter, in default order
+pub const SCORE_COLUMNS: [(&str, &str); 9] = [
+    ("semester", "学期"),
+    ("xkkh", "选课课号"),
+    ("course_code", "课程代码"),
+    ("course_name", "课程名称"),
+    ("score", "成绩"),
+    ("makeup_score", "补考成绩"),
+    ("credit", "学分"),
+    ("grade_point", "绩点"),
+    ("counted", "计入绩点"),
+];
+
+enum ScoreCell {
+    Text(String),
+    Number(f64),
+}
+
+// unknown keys are ignored, nothing selected means every column
+pub fn score_columns(columns: &Vec<String>) -> Vec<(&'static str, &'static str)> {
+    let selected = SCORE_COLUMNS
+        .iter()
+        .filter(|(key, _)| columns.iter().any(|column| column == key))
+        .cloned()
+        .collect::<Vec<_>>();
+    if selected.is_empty() {
+        SCORE_COLUMNS.to_vec()
+    } else {
+        selected
+    }
+}
+
+fn score_cell(record: &ScoreRecord, column: &str) -> ScoreCell {
+    match column {
+        "semester" => ScoreCell::Text(record.semester.clone()),
+        "xkkh" => ScoreCell::Text(record.xkkh.clone()),
+        "course_code" => ScoreCell::Text(record.course_code.clone()),
+        "course_name" => ScoreCell::Text(record.course_name.clone()),
+        "score" => ScoreCell::Text(record.score.clone()),
+        "makeup_score" => ScoreCell::Text(record.makeup_score.clone().unwrap_or_default()),
+        "credit" => ScoreCell::Number(record.credit),
+        "grade_point" => ScoreCell::Number(record.effective_grade_point),
+        "counted" => ScoreCell::Text(if record.counted { "是" } else { "否" }.to_string()),
+        _ => ScoreCell::Text("".to_string()),
+    }
+}
+
+// oldest semester first, zdbk order within a semester
+fn sorted_records(records: &Vec<ScoreRecord>) -> Vec<&ScoreRecord> {
+    let mut sorted = records.iter().collect::<Vec<_>>();
+    sorted.sort_by(|a, b| a.semester.cmp(&b.semester));
+    sorted
+}
+
+pub fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+pub fn score_to_csv(records: &Vec<ScoreRecord>, columns: &Vec<String>) -> String {
+    let columns = score_columns(columns);
+    // excel needs the bom to read utf-8
+    let mut csv = String::from("\u{feff}");
+    csv.push_str(
+        &columns
+            .iter()
+            .map(|(_, title)| csv_field(title))
+            .collect::<Vec<_>>()
+            .join(","),
+    );
+    csv.push_str("\r\n");
+    for record in sorted_records(records) {
+        let row = columns
+            .iter()
+            .map(|(key, _)| match score_cell(record, key) {
+                ScoreCell::Text(text) => csv_field(&text),
+                ScoreCell::Number(number) => number.to_string(),
+            })
+            .collect::<Vec<_>>();
+        csv.push_str(&row.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+pub fn score_to_xlsx(records: &Vec<ScoreRecord>, columns: &Vec<String>) -> Result<Vec<u8>, String> {
+    let columns = score_columns(columns);
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    sheet.set_name("成绩").map_err(|err| err.to_string())?;
+    let header = Format::new().set_bold();
+    for (col, (_, title)) in columns.iter().enumerate() {
+        sheet
+            .write_string_with_format(0, col as u16, *title, &header)
+            .map_err(|err| err.to_string())?;
+    }
+    for (row, record) in sorted_records(records).into_iter().enumerate() {
+        for (col, (key, _)) in columns.iter().enumerate() {
+            let (row, col) = (row as u32 + 1, col as u16);
+            match score_cell(record, key) {
+                ScoreCell::Text(text) => sheet.write_string(row, col, text),
+                ScoreCell::Number(number) => sheet.write_number(row, col, number),
+            }
+            .map_err(|err| err.to_string())?;
+        }
+    }
+    sheet.autofit();
+    workbook.save_to_buffer().map_err(|err| err.to_string())
+}
+
+// 2023-2024-1 is the autumn-winter semester of that academic year
+pub fn semester_name(semester: &str) -> String {
+    match semester.rsplit_once('-') {
+        Some((year, "1")) => format!("{} 学年秋冬学期", year),
+        Some((year, "2")) => format!("{} 学年春夏学期", year),
+        _ => semester.to_string(),
+    }
+}
+
+// cut the text with an ellipsis so it stays within the cell
+fn fit_text(text: &str, size: f32, max_width: f32) -> String {
+    if text_width(text, size) <= max_width {
+        return text.to_string();
+    }
+    let mut fitted = String::new();
+    for c in text.chars() {
+        fitted.push(c);
+        if text_width(&fitted, size) + size > max_width {
+            fitted.pop();
+            break;
+        }
+    }
+    fitted.push('…');
+    fitted
+}
+
+fn summary_line(summary: &GpaSummary) -> String {
+    format!(
+        "学分 {:.1}    均绩 {:.2}    4.3 制 {:.2}    4.0 制 {:.2}    加权均分 {:.2}",
+        summary.credit, summary.gpa, summary.gpa_4_3, summary.gpa_4_0, summary.average_score
+    )
+}
+
+pub fn score_to_pdf(records: &Vec<ScoreRecord>, username: &str) -> Vec<u8> {
+    let breakdown = gpa_breakdown(records, &GpaOptions::default());
+    let mut semesters: BTreeMap<&str, Vec<&ScoreRecord>> = BTreeMap::new();
+    for record in records.iter() {
+        semesters
+            .entry(record.semester.as_str())
+            .or_default()
+            .push(record);
+    }
+
+    let mut pdf = TextPdf::new();
+    pdf.paragraph("浙江大学成绩单", 18.0);
+    pdf.paragraph(
+        &format!(
+            "学号：{}    导出时间：{}",
+            username,
+            Local::now().format("%Y-%m-%d %H:%M")
+        ),
+        10.0,
+    );
+    pdf.rule();
+
+    // x offsets of code, name, credit, score and grade point
+    let cols = [0.0, 80.0, 330.0, 380.0, 440.0];
+    let name_width = cols[2] - cols[1] - 10.0;
+    for (semester, records) in semesters.iter() {
+        pdf.space(8.0);
+        pdf.paragraph(&semester_name(semester), 13.0);
+        pdf.row(
+            &[
+                (cols[0], "课程代码"),
+                (cols[1], "课程名称"),
+                (cols[2], "学分"),
+                (cols[3], "成绩"),
+                (cols[4], "绩点"),
+            ],
+            10.0,
+        );
+        for record in records.iter() {
+            let score = match &record.makeup_score {
+                Some(makeup_score) => format!("{}/{}", record.score, makeup_score),
+                None => record.score.clone(),
+            };
+            // superseded retakes and non-graded courses are marked
+            let grade_point = if record.counted {
+                format!("{:.1}", record.effective_grade_point)
+            } else {
+                "*".to_string()
+            };
+            pdf.row(
+                &[
+                    (cols[0], &record.course_code),
+                    (cols[1], &fit_text(&record.course_name, 10.0, name_width)),
+                    (cols[2], &format!("{:.1}", record.credit)),
+                    (cols[3], &score),
+                    (cols[4], &grade_point),
+                ],
+                10.0,
+            );
+        }
+        if let Some(summary) = breakdown
+            .semesters
+            .iter()
+            .find(|gpa| gpa.semester == *semester)
+        {
+            pdf.paragraph(&format!("本学期  {}", summary_line(&summary.summary)), 10.0);
+        }
+    }
+
+    pdf.space(8.0);
+    pdf.rule();
+    pdf.paragraph(
+        &format!("总计  {}", summary_line(&breakdown.cumulative)),
+        11.0,
+    );
+    pdf.paragraph(
+        "注：* 表示不计入绩点的课程（如合格制课程或已被重修覆盖的成绩）",
+        9.0,
+    );
+    pdf.finish()
+}
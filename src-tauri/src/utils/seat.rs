@@ -0,0 +1,34 @@
+use crate::model::SeatInfo;
+use crate::utils::json_text;
+use serde_json::Value;
+
+// rows of the selection query, capacity in rs or jxbrl and enrolled in yxrs or yxzrs
+pub fn parse_seat_info(item: &Value) -> Option<SeatInfo> {
+    let xkkh = json_text(item, &["xkkh"]);
+    if xkkh.is_empty() {
+        return None;
+    }
+    let capacity = json_text(item, &["rs", "jxbrl"]).parse().ok()?;
+    Some(SeatInfo {
+        xkkh,
+        course_name: json_text(item, &["kcmc"]),
+        teacher: json_text(item, &["jsxm", "jzgmc"]),
+        capacity,
+        enrolled: json_text(item, &["yxrs", "yxzrs"]).parse().unwrap_or(0),
+    })
+}
+
+pub fn free_seats(seat: &SeatInfo) -> i64 {
+    (seat.capacity - seat.enrolled).max(0)
+}
+
+pub fn describe_seat(seat: &SeatInfo) -> String {
+    format!(
+        "{}（{}）已选 {}/{}，余量 {}",
+        seat.course_name,
+        seat.teacher,
+        seat.enrolled,
+        seat.capacity,
+        free_seats(seat)
+    )
+}
@@ -0,0 +1,395 @@
+use crate::model::ClassSession;
+use crate::utils::{ics_datetime, IcsCalendar};
+use chrono::{Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+
+// class periods of the Zijingang, Yuquan, Xixi, Huajiachi and Zhijiang campuses,
+// campuses with another schedule can be set in `campus_period_times`
+const PERIOD_TIMES: [&str; 13] = [
+    "08:00-08:45",
+    "08:50-09:35",
+    "10:00-10:45",
+    "10:50-11:35",
+    "11:40-12:25",
+    "13:25-14:10",
+    "14:15-15:00",
+    "15:05-15:50",
+    "16:15-17:00",
+    "17:05-17:50",
+    "18:50-19:35",
+    "19:40-20:25",
+    "20:30-21:15",
+];
+
+const CAMPUSES: [&str; 8] = [
+    "紫金港",
+    "玉泉",
+    "西溪",
+    "华家池",
+    "之江",
+    "舟山",
+    "海宁",
+    "宁波",
+];
+
+pub fn campus_of(location: &str) -> Option<String> {
+    CAMPUSES
+        .iter()
+        .find(|campus| location.contains(*campus))
+        .map(|campus| campus.to_string())
+}
+
+// clock time of a period range as ("HH:MM", "HH:MM")
+pub fn period_range_time(
+    campus: Option<&str>,
+    start_period: u32,
+    end_period: u32,
+    campus_period_times: &HashMap<String, Vec<String>>,
+) -> Option<(String, String)> {
+    let default_times = PERIOD_TIMES.iter().map(|time| time.to_string()).collect();
+    let times: &Vec<String> = campus
+        .and_then(|campus| campus_period_times.get(campus))
+        .unwrap_or(&default_times);
+    let start = times.get(start_period.checked_sub(1)? as usize)?;
+    let end = times.get(end_period.checked_sub(1)? as usize)?;
+    Some((
+        start.split('-').next()?.trim().to_string(),
+        end.split('-').nth(1)?.trim().to_string(),
+    ))
+}
+
+// fill campus and clock times of sessions
+pub fn normalize_sessions(
+    sessions: &mut Vec<ClassSession>,
+    campus_period_times: &HashMap<String, Vec<String>>,
+) {
+    for session in sessions.iter_mut() {
+        session.campus = campus_of(&session.location);
+        if let Some((start_time, end_time)) = period_range_time(
+            session.campus.as_deref(),
+            session.start_period,
+            session.end_period,
+            campus_period_times,
+        ) {
+            session.start_time = Some(start_time);
+            session.end_time = Some(end_time);
+        }
+    }
+}
+
+fn parse_weekday(text: &str) -> Option<u32> {
+    match text {
+        "一" | "1" => Some(1),
+        "二" | "2" => Some(2),
+        "三" | "3" => Some(3),
+        "四" | "4" => Some(4),
+        "五" | "5" => Some(5),
+        "六" | "6" => Some(6),
+        "日" | "天" | "七" | "7" => Some(7),
+        _ => None,
+    }
+}
+
+// "1,2,3" or "1-8,10-16"
+fn parse_numbers(text: &str) -> Vec<u32> {
+    let mut numbers = Vec::new();
+    for part in text.split([',', '，', '、']) {
+        let mut range = part.split(['-', '~', '－']);
+        let start = range.next().and_then(|n| n.trim().parse::<u32>().ok());
+        let end = range.next().and_then(|n| n.trim().parse::<u32>().ok());
+        match (start, end) {
+            (Some(start), Some(end)) if start <= end => numbers.extend(start..=end),
+            (Some(start), None) => numbers.push(start),
+            _ => {}
+        }
+    }
+    numbers
+}
+
+// one session in text form, like "周一第1,2节{第1-16周|单周} 紫金港西1-101"
+pub fn parse_session_text(text: &str) -> Option<(u32, u32, u32, Vec<u32>, String)> {
+    let re_weekday = Regex::new(r"(?:周|星期)([一二三四五六日天七])").unwrap();
+    let re_periods = Regex::new(r"第([\d,，\-~]+)节").unwrap();
+    let re_weeks = Regex::new(r"第?([\d,，\-~]+)周").unwrap();
+
+    let weekday = parse_weekday(re_weekday.captures(text)?.get(1)?.as_str())?;
+    let periods = parse_numbers(re_periods.captures(text)?.get(1)?.as_str());
+    let start_period = *periods.iter().min()?;
+    let end_period = *periods.iter().max()?;
+
+    let mut weeks = match re_weeks.captures(text) {
+        Some(caps) => parse_numbers(&caps[1]),
+        None => (1..=16).collect(),
+    };
+    if text.contains("单周") {
+        weeks.retain(|week| week % 2 == 1);
+    } else if text.contains("双周") {
+        weeks.retain(|week| week % 2 == 0);
+    }
+    weeks.sort();
+    weeks.dedup();
+
+    let location = re_weekday.replace_all(text, "");
+    let location = re_periods.replace_all(&location, "");
+    let location = re_weeks.replace_all(&location, "");
+    let location = location
+        .replace("单周", "")
+        .replace("双周", "")
+        .replace(['{', '}', '(', ')', '（', '）', '|'], " ");
+    let location = location.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    Some((weekday, start_period, end_period, weeks, location))
+}
+
+// `classroom_schedule` of a course from /api/my-courses, either a string with
+// sessions separated by `;` or line breaks, or an array of such strings
+pub fn parse_classroom_schedule(course: &Value) -> Vec<ClassSession> {
+    let texts = match &course["classroom_schedule"] {
+        Value::String(text) => vec![text.clone()],
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| match item {
+                Value::String(text) => Some(text.clone()),
+                Value::Object(_) => item["schedule"]
+                    .as_str()
+                    .or(item["time"].as_str())
+                    .map(|time| format!("{} {}", time, item["location"].as_str().unwrap_or(""))),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    let course_id = course["id"].as_i64().unwrap_or(0).to_string();
+    let course_name = course["name"].as_str().unwrap_or("").to_string();
+    let start_date = course["start_date"]
+        .as_str()
+        .map(|date| date.chars().take(10).collect::<String>());
+
+    texts
+        .iter()
+        .flat_map(|text| {
+            text.split(|c| c == ';' || c == '；' || c == '\n')
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+        })
+        .filter_map(|text| parse_session_text(&text))
+        .map(
+            |(weekday, start_period, end_period, weeks, location)| ClassSession {
+                course_id: course_id.clone(),
+                course_name: course_name.clone(),
+                weekday,
+                start_period,
+                end_period,
+                weeks,
+                location,
+                campus: None,
+                start_time: None,
+                end_time: None,
+                start_date: start_date.clone(),
+            },
+        )
+        .collect()
+}
+
+fn value_to_u32(value: &Value) -> Option<u32> {
+    match value {
+        Value::Number(n) => n.as_u64().map(|n| n as u32),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+// `kbList` of the zdbk timetable, each item is one weekly session with `kcb`
+// like "课程名<br>秋冬{第1-8周}<br>教师<br>紫金港西1-101zwf"
+pub fn parse_zdbk_timetable(json: &Value) -> Vec<ClassSession> {
+    let re_weeks = Regex::new(r"第?([\d,，\-~]+)周").unwrap();
+    let re_break = Regex::new(r"(?i)<br\s*/?>").unwrap();
+    let mut sessions = Vec::new();
+    for item in json["kbList"].as_array().unwrap_or(&Vec::new()) {
+        let kcb = item["kcb"].as_str().unwrap_or("");
+        let parts = re_break.split(kcb).map(|s| s.trim()).collect::<Vec<_>>();
+        let weekday = match value_to_u32(&item["xqj"]) {
+            Some(weekday) if (1..=7).contains(&weekday) => weekday,
+            _ => continue,
+        };
+        let start_period = match value_to_u32(&item["djj"]) {
+            Some(start_period) => start_period,
+            None => continue,
+        };
+        let length = value_to_u32(&item["skcd"]).unwrap_or(1).max(1);
+
+        let mut weeks = re_weeks
+            .captures(kcb)
+            .map(|caps| parse_numbers(&caps[1]))
+            .unwrap_or((1..=16).collect());
+        let parity = item["dsz"].as_str().unwrap_or("");
+        if parity.contains('单') || kcb.contains("单周") {
+            weeks.retain(|week| week % 2 == 1);
+        } else if parity.contains('双') || kcb.contains("双周") {
+            weeks.retain(|week| week % 2 == 0);
+        }
+        weeks.sort();
+        weeks.dedup();
+
+        let course_name = item["kcmc"]
+            .as_str()
+            .or(parts.first().copied())
+            .unwrap_or("")
+            .to_string();
+        // the room is followed by a "zwf" (seat) marker on zdbk
+        let location = item["cdmc"]
+            .as_str()
+            .or(parts.get(3).copied())
+            .unwrap_or("")
+            .split("zwf")
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        sessions.push(ClassSession {
+            course_id: item["xkkh"]
+                .as_str()
+                .or(item["kcdm"].as_str())
+                .unwrap_or("")
+                .to_string(),
+            course_name,
+            weekday,
+            start_period,
+            end_period: start_period + length - 1,
+            weeks,
+            location,
+            campus: None,
+            start_time: None,
+            end_time: None,
+            start_date: None,
+        });
+    }
+    sessions
+}
+
+pub fn session_uid(session: &ClassSession) -> String {
+    format!(
+        "class-{}-{}-{}-{}@courses.zju.edu.cn",
+        session.course_id,
+        session.weekday,
+        session.start_period,
+        session.weeks.first().unwrap_or(&0)
+    )
+}
+
+// week 1 starts on the monday of the week containing the start date
+fn first_monday(start_date: NaiveDate) -> NaiveDate {
+    start_date - Duration::days(start_date.weekday().num_days_from_monday() as i64)
+}
+
+fn local_time(date: NaiveDate, time: NaiveTime) -> String {
+    NaiveDateTime::new(date, time)
+        .format("%Y%m%dT%H%M%S")
+        .to_string()
+}
+
+fn write_session(
+    calendar: &mut IcsCalendar,
+    session: &ClassSession,
+    semester_start: Option<NaiveDate>,
+    now: &str,
+) -> Option<()> {
+    let start_date = session
+        .start_date
+        .as_ref()
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+        .or(semester_start)?;
+    let monday = first_monday(start_date);
+    let first_week = *session.weeks.first()?;
+    let last_week = *session.weeks.last()?;
+    let day = |week: u32| {
+        monday + Duration::weeks(week as i64 - 1) + Duration::days(session.weekday as i64 - 1)
+    };
+    let (start_time, end_time) = match (&session.start_time, &session.end_time) {
+        (Some(start_time), Some(end_time)) => (start_time.clone(), end_time.clone()),
+        _ => period_range_time(
+            None,
+            session.start_period,
+            session.end_period,
+            &HashMap::new(),
+        )?,
+    };
+    let start_time = NaiveTime::parse_from_str(&start_time, "%H:%M").ok()?;
+    let end_time = NaiveTime::parse_from_str(&end_time, "%H:%M").ok()?;
+
+    // odd/even weeks become an interval of 2, other gaps are excluded dates
+    let interval = if session.weeks.len() > 1 && session.weeks.windows(2).all(|w| w[1] - w[0] == 2)
+    {
+        2
+    } else {
+        1
+    };
+    let excluded = (first_week..=last_week)
+        .step_by(interval)
+        .filter(|week| !session.weeks.contains(week))
+        .collect::<Vec<_>>();
+
+    calendar.begin("VEVENT");
+    calendar.text("UID", &session_uid(session));
+    calendar.property("DTSTAMP", now);
+    calendar.text("SUMMARY", &session.course_name);
+    if !session.location.is_empty() {
+        calendar.text("LOCATION", &session.location);
+    }
+    calendar.text(
+        "DESCRIPTION",
+        &format!("第 {}-{} 节", session.start_period, session.end_period),
+    );
+    calendar.property(
+        "DTSTART;TZID=Asia/Shanghai",
+        &local_time(day(first_week), start_time),
+    );
+    calendar.property(
+        "DTEND;TZID=Asia/Shanghai",
+        &local_time(day(first_week), end_time),
+    );
+    if last_week > first_week {
+        let until = FixedOffset::east_opt(8 * 3600)
+            .unwrap()
+            .from_local_datetime(&NaiveDateTime::new(day(last_week), end_time))
+            .unwrap()
+            .with_timezone(&Utc);
+        calendar.property(
+            "RRULE",
+            &format!(
+                "FREQ=WEEKLY;INTERVAL={};UNTIL={}",
+                interval,
+                ics_datetime(&until)
+            ),
+        );
+    }
+    for week in excluded {
+        calendar.property(
+            "EXDATE;TZID=Asia/Shanghai",
+            &local_time(day(week), start_time),
+        );
+    }
+    calendar.end("VEVENT");
+    Some(())
+}
+
+pub fn timetable_to_ics(sessions: &Vec<ClassSession>, semester_start: Option<NaiveDate>) -> String {
+    let now = ics_datetime(&Utc::now());
+    let mut calendar = IcsCalendar::new("浙江大学课表");
+    calendar.shanghai_timezone();
+    for session in sessions.iter() {
+        write_session(&mut calendar, session, semester_start, &now);
+    }
+    calendar.finish()
+}
+
+// a calendar object resource for CalDAV
+pub fn session_to_ics(session: &ClassSession, semester_start: Option<NaiveDate>) -> Option<String> {
+    let now = ics_datetime(&Utc::now());
+    let mut calendar = IcsCalendar::new("浙江大学课表");
+    calendar.shanghai_timezone();
+    write_session(&mut calendar, session, semester_start, &now)?;
+    Some(calendar.finish())
+}
@@ -0,0 +1,158 @@
+use crate::utils::todo_url;
+use chrono::{DateTime, Local, Utc};
+use serde_json::{json, Value};
+
+pub trait TodoExporter {
+    // shown in the save dialog filter
+    fn name(&self) -> &'static str;
+    fn extension(&self) -> &'static str;
+    fn export(&self, todo_list: &Vec<Value>) -> String;
+}
+
+pub fn todo_exporter(format: &str) -> Option<Box<dyn TodoExporter + Send>> {
+    match format {
+        "md" => Some(Box::new(MarkdownExporter)),
+        "json" => Some(Box::new(JsonExporter)),
+        "org" => Some(Box::new(OrgExporter)),
+        "txt" => Some(Box::new(TodoTxtExporter)),
+        _ => None,
+    }
+}
+
+fn end_time(todo: &Value) -> Option<DateTime<Local>> {
+    todo["end_time"]
+        .as_str()
+        .and_then(|end_time| end_time.parse::<DateTime<Utc>>().ok())
+        .map(|end_time| end_time.with_timezone(&Local))
+}
+
+// titles are single line in every format
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub struct MarkdownExporter;
+
+impl TodoExporter for MarkdownExporter {
+    fn name(&self) -> &'static str {
+        "Markdown"
+    }
+
+    fn extension(&self) -> &'static str {
+        "md"
+    }
+
+    fn export(&self, todo_list: &Vec<Value>) -> String {
+        let mut md = String::from("# 学在浙大待办事项\n\n");
+        for todo in todo_list.iter() {
+            let title = one_line(todo["title"].as_str().unwrap_or(""));
+            let course_name = todo["course_name"].as_str().unwrap_or("");
+            let deadline = match end_time(todo) {
+                Some(end_time) => format!("截止：{}", end_time.format("%Y-%m-%d %H:%M")),
+                None => "无截止时间".to_string(),
+            };
+            md.push_str(&format!(
+                "- [ ] [{}]({}) · {} · {}\n",
+                title.replace('[', "\\[").replace(']', "\\]"),
+                todo_url(todo),
+                course_name,
+                deadline
+            ));
+        }
+        md
+    }
+}
+
+pub struct JsonExporter;
+
+impl TodoExporter for JsonExporter {
+    fn name(&self) -> &'static str {
+        "JSON"
+    }
+
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn export(&self, todo_list: &Vec<Value>) -> String {
+        let todos = todo_list
+            .iter()
+            .map(|todo| {
+                json!({
+                    "id": todo["id"],
+                    "course_id": todo["course_id"],
+                    "course_name": todo["course_name"],
+                    "title": todo["title"],
+                    "type": todo["type"],
+                    "end_time": todo["end_time"],
+                    "url": todo_url(todo),
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::to_string_pretty(&todos).unwrap_or_default()
+    }
+}
+
+pub struct OrgExporter;
+
+impl TodoExporter for OrgExporter {
+    fn name(&self) -> &'static str {
+        "Org"
+    }
+
+    fn extension(&self) -> &'static str {
+        "org"
+    }
+
+    fn export(&self, todo_list: &Vec<Value>) -> String {
+        let mut org = String::from("#+TITLE: 学在浙大待办事项\n\n");
+        for todo in todo_list.iter() {
+            let title = one_line(todo["title"].as_str().unwrap_or(""));
+            let course_name = todo["course_name"].as_str().unwrap_or("");
+            org.push_str(&format!("* TODO {}\n", title));
+            if let Some(end_time) = end_time(todo) {
+                org.push_str(&format!(
+                    "  DEADLINE: <{}>\n",
+                    end_time.format("%Y-%m-%d %a %H:%M")
+                ));
+            }
+            org.push_str("  :PROPERTIES:\n");
+            org.push_str(&format!("  :COURSE:   {}\n", course_name));
+            org.push_str(&format!("  :URL:      {}\n", todo_url(todo)));
+            org.push_str("  :END:\n");
+        }
+        org
+    }
+}
+
+// reference:
+// https://github.com/todotxt/todo.txt
+pub struct TodoTxtExporter;
+
+impl TodoExporter for TodoTxtExporter {
+    fn name(&self) -> &'static str {
+        "todo.txt"
+    }
+
+    fn extension(&self) -> &'static str {
+        "txt"
+    }
+
+    fn export(&self, todo_list: &Vec<Value>) -> String {
+        let mut txt = String::new();
+        for todo in todo_list.iter() {
+            let title = one_line(todo["title"].as_str().unwrap_or(""));
+            // projects cannot contain spaces
+            let project = one_line(todo["course_name"].as_str().unwrap_or("")).replace(' ', "_");
+            txt.push_str(&title);
+            if !project.is_empty() {
+                txt.push_str(&format!(" +{}", project));
+            }
+            if let Some(end_time) = end_time(todo) {
+                txt.push_str(&format!(" due:{}", end_time.format("%Y-%m-%d")));
+            }
+            txt.push_str(&format!(" url:{}\n", todo_url(todo)));
+        }
+        txt
+    }
+}
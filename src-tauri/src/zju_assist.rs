@@ -1,4 +1,5 @@
 use anyhow::{anyhow, Ok, Result};
+use chrono::{DateTime, FixedOffset, Utc};
 use log::{debug, info};
 use percent_encoding::percent_decode_str;
 use regex::Regex;
@@ -15,8 +16,15 @@ use std::{fs::File, io::Write, path::Path};
 use url::Url;
 use serde::Deserialize;
 
-use crate::model::Subject;
-use crate::utils::{measure_latency, rsa_no_padding};
+use crate::model::{
+    ClassSession, CourseAnnouncement, EvaluationCourse, Exam, ExamKind, HomeworkStatus, PlanCourse, QuizActivity,
+    QuizAttempt, QuizQuestion, SeatInfo, Subject, Topic, TopicReply, TrainingPlan, VideoActivity,
+};
+use crate::utils::{
+    measure_latency, parse_evaluation_course, parse_evaluation_window, parse_exam_time,
+    parse_plan_category, parse_plan_course, parse_seat_info, parse_zdbk_timetable,
+    rsa_no_padding,
+};
 
 #[derive(Clone)]
 pub struct ZjuAssist {
@@ -372,11 +380,10 @@ impl ZjuAssist {
         Ok(courses)
     }
 
-    pub async fn get_activities_uploads(&self, course_id: i64) -> Result<Vec<Value>> {
+    pub async fn get_activities(&self, course_id: i64) -> Result<Vec<Value>> {
         if !self.have_login {
             return Err(anyhow!("Not login"));
         }
-        let mut uploads = Vec::new();
         let res = self
             .get(format!(
                 "https://courses.zju.edu.cn/api/courses/{}/activities",
@@ -385,8 +392,17 @@ impl ZjuAssist {
             .send()
             .await?;
         let json: Value = res.json().await?;
-        let activities = json["activities"].as_array().unwrap();
+        Ok(json["activities"].as_array().unwrap().iter().cloned().collect())
+    }
+
+    pub async fn get_activities_uploads(&self, course_id: i64) -> Result<Vec<Value>> {
+        let mut uploads = Vec::new();
+        let activities = self.get_activities(course_id).await?;
         for activity in activities {
+            if activity["type"].as_str() == Some("online_video") {
+                // videos are handled by get_video_activities
+                continue;
+            }
             if activity["uploads"].is_array() {
                 uploads.extend(activity["uploads"].as_array().unwrap().iter().cloned());
             }
@@ -394,38 +410,520 @@ impl ZjuAssist {
         Ok(uploads)
     }
 
-    pub async fn get_homework_uploads(&self, course_id: i64) -> Result<Vec<Value>> {
+    pub async fn get_video_activities(&self, course_id: i64) -> Result<Vec<VideoActivity>> {
+        let mut videos = Vec::new();
+        let activities = self.get_activities(course_id).await?;
+        for activity in activities {
+            if activity["type"].as_str() != Some("online_video") {
+                continue;
+            }
+            let id = activity["id"].as_i64().unwrap();
+            let title = activity["title"].as_str().unwrap_or("").replace("/", "-");
+            let uploads = activity["uploads"].as_array().cloned().unwrap_or_default();
+            for (index, upload) in uploads.iter().enumerate() {
+                // prefer the transcoded stream, fall back to the original file
+                let url = upload["videos"]
+                    .as_array()
+                    .and_then(|videos| {
+                        videos
+                            .iter()
+                            .filter_map(|video| video["url"].as_str())
+                            .find(|url| !url.contains(".m3u8"))
+                    })
+                    .map(|url| url.to_string())
+                    .unwrap_or(format!(
+                        "https://courses.zju.edu.cn/api/uploads/reference/{}/blob",
+                        upload["reference_id"].as_i64().unwrap_or(0)
+                    ));
+                let ext = Path::new(upload["name"].as_str().unwrap_or(""))
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .unwrap_or("mp4")
+                    .to_string();
+                let file_name = if uploads.len() > 1 {
+                    format!("{}-{}.{}", title, index + 1, ext)
+                } else {
+                    format!("{}.{}", title, ext)
+                };
+                videos.push(VideoActivity {
+                    id,
+                    course_id,
+                    course_name: "".to_string(), // course name will be set by caller
+                    file_name,
+                    url,
+                    path: "".to_string(), // path will be set when downloading
+                    size: upload["size"].as_u64().unwrap_or(0),
+                });
+            }
+            // external media linked by the teacher
+            if uploads.is_empty() {
+                if let Some(url) = activity["data"]["link"]
+                    .as_str()
+                    .or(activity["data"]["url"].as_str())
+                {
+                    let ext = Path::new(url.split('?').next().unwrap_or(""))
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .unwrap_or("")
+                        .to_lowercase();
+                    if ["mp4", "flv", "mov", "mkv", "webm"].contains(&ext.as_str()) {
+                        videos.push(VideoActivity {
+                            id,
+                            course_id,
+                            course_name: "".to_string(),
+                            file_name: format!("{}.{}", title, ext),
+                            url: url.to_string(),
+                            path: "".to_string(),
+                            size: 0,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(videos)
+    }
+
+    pub async fn get_video_response(&self, url: &str) -> Result<Response> {
+        let res = self.get(url).send().await?;
+        if !res.status().is_success() {
+            return Err(anyhow!("Failed to get video response: {}", res.status()));
+        }
+        Ok(res)
+    }
+
+    pub async fn get_user_id(&self) -> Result<i64> {
         if !self.have_login {
             return Err(anyhow!("Not login"));
         }
-        let mut uploads = Vec::new();
-        let res = self.get(format!("https://courses.zju.edu.cn/api/courses/{}/homework-activities?conditions=%7B%22itemsSortBy%22:%7B%22predicate%22:%22module%22,%22reverse%22:false%7D%7D&page=1&page_size=20&reloadPage=false", course_id))
+        let res = self
+            .get("https://courses.zju.edu.cn/api/profile")
             .send()
             .await?;
         let json: Value = res.json().await?;
-        let homeworks = json["homework_activities"].as_array().unwrap();
-        for homework in homeworks {
-            if homework["uploads"].is_array() {
-                uploads.extend(homework["uploads"].as_array().unwrap().iter().cloned());
-            }
+        json["id"].as_i64().ok_or(anyhow!("User id not found"))
+    }
+
+    pub async fn get_homework_activities(&self, course_id: i64) -> Result<Vec<Value>> {
+        if !self.have_login {
+            return Err(anyhow!("Not login"));
         }
+        let mut homeworks = Vec::new();
+        let res = self.get(format!("https://courses.zju.edu.cn/api/courses/{}/homework-activities?conditions=%7B%22itemsSortBy%22:%7B%22predicate%22:%22module%22,%22reverse%22:false%7D%7D&page=1&page_size=20&reloadPage=false", course_id))
+            .send()
+            .await?;
+        let json: Value = res.json().await?;
+        homeworks.extend(json["homework_activities"].as_array().unwrap().iter().cloned());
         if json["pages"].as_i64().unwrap() > 1 {
             for page in 2..=json["pages"].as_i64().unwrap() {
                 let res = self.get(format!("https://courses.zju.edu.cn/api/courses/{}/homework-activities?conditions=%7B%22itemsSortBy%22:%7B%22predicate%22:%22module%22,%22reverse%22:false%7D%7D&page={}&page_size=20&reloadPage=false", course_id, page))
                     .send()
                     .await?;
                 let json: Value = res.json().await?;
-                let homeworks = json["homework_activities"].as_array().unwrap();
-                for homework in homeworks {
-                    if homework["uploads"].is_array() {
-                        uploads.extend(homework["uploads"].as_array().unwrap().iter().cloned());
-                    }
-                }
+                homeworks.extend(json["homework_activities"].as_array().unwrap().iter().cloned());
+            }
+        }
+        Ok(homeworks)
+    }
+
+    pub async fn get_homework_uploads(&self, course_id: i64) -> Result<Vec<Value>> {
+        let mut uploads = Vec::new();
+        let homeworks = self.get_homework_activities(course_id).await?;
+        for homework in homeworks {
+            if homework["uploads"].is_array() {
+                uploads.extend(homework["uploads"].as_array().unwrap().iter().cloned());
             }
         }
         Ok(uploads)
     }
 
+    pub async fn get_homework_submissions(
+        &self,
+        activity_id: i64,
+        user_id: i64,
+    ) -> Result<Vec<Value>> {
+        if !self.have_login {
+            return Err(anyhow!("Not login"));
+        }
+        let res = self
+            .get(format!(
+                "https://courses.zju.edu.cn/api/activities/{}/students/{}/submission_list",
+                activity_id, user_id
+            ))
+            .send()
+            .await?;
+        let json: Value = res.json().await?;
+        Ok(json["list"]
+            .as_array()
+            .map(|list| list.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    pub async fn get_homework_status(
+        &self,
+        course_id: i64,
+        user_id: i64,
+    ) -> Result<Vec<HomeworkStatus>> {
+        let homeworks = self.get_homework_activities(course_id).await?;
+        let mut statuses = Vec::new();
+        for homework in homeworks {
+            // submissions are looked up by the id, nothing to show without it
+            let id = match homework["id"].as_i64() {
+                Some(id) => id,
+                None => continue,
+            };
+            let end_time = homework["end_time"].as_str().map(|s| s.to_string());
+            let mut submissions = self.get_homework_submissions(id, user_id).await?;
+            // the latest submission is the one that counts
+            submissions.sort_by_key(|submission| {
+                submission["submitted_at"]
+                    .as_str()
+                    .or(submission["created_at"].as_str())
+                    .unwrap_or("")
+                    .to_string()
+            });
+            let latest = submissions.last();
+
+            let submitted_at = latest.and_then(|submission| {
+                submission["submitted_at"]
+                    .as_str()
+                    .or(submission["created_at"].as_str())
+                    .map(|s| s.to_string())
+            });
+            let is_late = match latest.and_then(|submission| submission["is_late"].as_bool()) {
+                Some(is_late) => is_late,
+                None => match (&submitted_at, &end_time) {
+                    (Some(submitted_at), Some(end_time)) => submitted_at
+                        .parse::<DateTime<Utc>>()
+                        .ok()
+                        .zip(end_time.parse::<DateTime<Utc>>().ok())
+                        .map_or(false, |(submitted_at, end_time)| submitted_at > end_time),
+                    _ => false,
+                },
+            };
+            let score = latest
+                .map(|submission| &submission["score"])
+                .filter(|score| !score.is_null())
+                .or(Some(&homework["score"]).filter(|score| !score.is_null()))
+                .map(|score| match score.as_str() {
+                    Some(score) => score.to_string(),
+                    None => score.to_string(),
+                });
+            let comment = latest
+                .and_then(|submission| {
+                    submission["instructor_comment"]
+                        .as_str()
+                        .or(submission["comment"].as_str())
+                })
+                .unwrap_or("")
+                .to_string();
+            let submission_uploads = latest
+                .and_then(|submission| submission["uploads"].as_array())
+                .map(|uploads| uploads.iter().cloned().collect())
+                .unwrap_or_default();
+            // teacher's returned / annotated files
+            let feedback_uploads = latest
+                .and_then(|submission| {
+                    ["marked_attachments", "correct_uploads", "instructor_comment_uploads"]
+                        .iter()
+                        .find_map(|key| submission[*key].as_array())
+                })
+                .map(|uploads| uploads.iter().cloned().collect())
+                .unwrap_or_default();
+
+            statuses.push(HomeworkStatus {
+                id,
+                course_id,
+                title: homework["title"].as_str().unwrap_or("").to_string(),
+                start_time: homework["start_time"].as_str().map(|s| s.to_string()),
+                end_time,
+                submitted: latest.is_some() || homework["submitted"].as_bool().unwrap_or(false),
+                submitted_at,
+                is_late,
+                score,
+                comment,
+                submission_uploads,
+                feedback_uploads,
+            });
+        }
+        Ok(statuses)
+    }
+
+    pub async fn get_quiz_attempt(&self, quiz_id: i64, submission: &Value) -> Result<QuizAttempt> {
+        let id = submission["id"]
+            .as_i64()
+            .ok_or(anyhow!("Submission id not found"))?;
+        let res = self
+            .get(format!(
+                "https://courses.zju.edu.cn/api/exams/{}/submissions/{}",
+                quiz_id, id
+            ))
+            .send()
+            .await?;
+        let json: Value = res.json().await?;
+
+        // answers are given as option ids, show them as option labels
+        let option_labels = |options: &Vec<Value>, ids: &Value| -> Option<String> {
+            let ids = ids.as_array()?;
+            let labels = ids
+                .iter()
+                .filter_map(|id| {
+                    options
+                        .iter()
+                        .position(|option| option["id"] == *id)
+                        .map(|index| ((b'A' + index as u8) as char).to_string())
+                })
+                .collect::<Vec<_>>();
+            Some(labels.join(""))
+        };
+        let empty = Vec::new();
+        let answers = json["submission_data"]["subjects"]
+            .as_array()
+            .unwrap_or(&empty);
+        let correct_answers = json["correct_answers_data"]["correct_answers"]
+            .as_array()
+            .unwrap_or(&empty);
+        let subject_scores = json["submission_score_data"]
+            .as_object()
+            .cloned()
+            .unwrap_or_default();
+
+        let mut questions = Vec::new();
+        for subject in json["subjects_data"]["subjects"]
+            .as_array()
+            .unwrap_or(&empty)
+        {
+            // answers and scores are matched by id, a subject without one is unusable
+            let subject_id = match subject["id"].as_i64() {
+                Some(subject_id) => subject_id,
+                None => continue,
+            };
+            let options = subject["options"].as_array().cloned().unwrap_or_default();
+            let answer = answers
+                .iter()
+                .find(|answer| answer["subject_id"].as_i64() == Some(subject_id))
+                .and_then(|answer| {
+                    option_labels(&options, &answer["answer_option_ids"])
+                        .filter(|labels| !labels.is_empty())
+                        .or(answer["answer"].as_str().map(|s| s.to_string()))
+                })
+                .unwrap_or_default();
+            let correct_answer = correct_answers
+                .iter()
+                .find(|answer| answer["subject_id"].as_i64() == Some(subject_id))
+                .and_then(|answer| {
+                    option_labels(&options, &answer["answer_option_ids"])
+                        .filter(|labels| !labels.is_empty())
+                        .or(answer["content"].as_str().map(|s| s.to_string()))
+                });
+            questions.push(QuizQuestion {
+                id: subject_id,
+                description: subject["description"].as_str().unwrap_or("").to_string(),
+                options: options
+                    .iter()
+                    .map(|option| option["content"].as_str().unwrap_or("").to_string())
+                    .collect(),
+                answer,
+                correct_answer,
+                point: subject["point"]
+                    .as_f64()
+                    .or(subject["point"].as_str().and_then(|s| s.parse().ok())),
+                score: subject_scores
+                    .get(&subject_id.to_string())
+                    .and_then(|score| score.as_f64().or(score.as_str()?.parse().ok())),
+            });
+        }
+
+        Ok(QuizAttempt {
+            id,
+            score: submission["score"]
+                .as_f64()
+                .or(submission["score"].as_str().and_then(|s| s.parse().ok())),
+            submitted_at: submission["submitted_at"]
+                .as_str()
+                .or(submission["created_at"].as_str())
+                .map(|s| s.to_string()),
+            questions,
+        })
+    }
+
+    pub async fn get_quiz_activities(&self, course_id: i64) -> Result<Vec<QuizActivity>> {
+        if !self.have_login {
+            return Err(anyhow!("Not login"));
+        }
+        let res = self
+            .get(format!(
+                "https://courses.zju.edu.cn/api/courses/{}/exams",
+                course_id
+            ))
+            .send()
+            .await?;
+        let json: Value = res.json().await?;
+        let mut quizzes = Vec::new();
+        for exam in json["exams"].as_array().unwrap_or(&Vec::new()) {
+            let id = match exam["id"].as_i64() {
+                Some(id) => id,
+                None => continue,
+            };
+            let res = self
+                .get(format!(
+                    "https://courses.zju.edu.cn/api/exams/{}/submissions",
+                    id
+                ))
+                .send()
+                .await?;
+            let submissions: Value = res.json().await?;
+            let mut attempts = Vec::new();
+            for submission in submissions["submissions"].as_array().unwrap_or(&Vec::new()) {
+                // attempts without an id can't be fetched, skip them
+                if submission["id"].as_i64().is_none() {
+                    continue;
+                }
+                attempts.push(self.get_quiz_attempt(id, submission).await?);
+            }
+            quizzes.push(QuizActivity {
+                id,
+                course_id,
+                title: exam["title"].as_str().unwrap_or("").to_string(),
+                start_time: exam["start_time"].as_str().map(|s| s.to_string()),
+                end_time: exam["end_time"].as_str().map(|s| s.to_string()),
+                total_points: exam["total_points"].as_f64(),
+                is_closed: exam["is_closed"].as_bool().unwrap_or(false),
+                attempts,
+            });
+        }
+        Ok(quizzes)
+    }
+
+    pub async fn get_topic_replies(&self, topic_id: i64) -> Result<Vec<TopicReply>> {
+        if !self.have_login {
+            return Err(anyhow!("Not login"));
+        }
+        let mut replies = Vec::new();
+        let mut page = 1;
+        loop {
+            let res = self
+                .get(format!(
+                    "https://courses.zju.edu.cn/api/topics/{}/replies?page={}&page_size=100",
+                    topic_id, page
+                ))
+                .send()
+                .await?;
+            let json: Value = res.json().await?;
+            for reply in json["replies"].as_array().unwrap_or(&Vec::new()) {
+                // a reply without an id cannot be told apart, skip it
+                let id = match reply["id"].as_i64() {
+                    Some(id) => id,
+                    None => continue,
+                };
+                replies.push(TopicReply {
+                    id,
+                    author: reply["created_by"]["name"]
+                        .as_str()
+                        .or(reply["user"]["name"].as_str())
+                        .unwrap_or("")
+                        .to_string(),
+                    content: reply["content"].as_str().unwrap_or("").to_string(),
+                    created_at: reply["created_at"].as_str().unwrap_or("").to_string(),
+                    uploads: reply["uploads"]
+                        .as_array()
+                        .map(|uploads| uploads.iter().cloned().collect())
+                        .unwrap_or_default(),
+                });
+            }
+            if page >= json["pages"].as_i64().unwrap_or(1) {
+                break;
+            }
+            page += 1;
+        }
+        Ok(replies)
+    }
+
+    pub async fn get_course_topics(&self, course_id: i64) -> Result<Vec<Topic>> {
+        if !self.have_login {
+            return Err(anyhow!("Not login"));
+        }
+        let mut topics = Vec::new();
+        let mut page = 1;
+        loop {
+            let res = self
+                .get(format!(
+                    "https://courses.zju.edu.cn/api/courses/{}/topics?page={}&page_size=20",
+                    course_id, page
+                ))
+                .send()
+                .await?;
+            let json: Value = res.json().await?;
+            for topic in json["topics"].as_array().unwrap_or(&Vec::new()) {
+                // the replies and the export file are keyed by the id
+                let id = match topic["id"].as_i64() {
+                    Some(id) => id,
+                    None => continue,
+                };
+                topics.push(Topic {
+                    id,
+                    course_id,
+                    title: topic["title"].as_str().unwrap_or("").to_string(),
+                    author: topic["created_by"]["name"]
+                        .as_str()
+                        .or(topic["user"]["name"].as_str())
+                        .unwrap_or("")
+                        .to_string(),
+                    content: topic["content"].as_str().unwrap_or("").to_string(),
+                    created_at: topic["created_at"].as_str().unwrap_or("").to_string(),
+                    updated_at: topic["updated_at"]
+                        .as_str()
+                        .or(topic["created_at"].as_str())
+                        .unwrap_or("")
+                        .to_string(),
+                    uploads: topic["uploads"]
+                        .as_array()
+                        .map(|uploads| uploads.iter().cloned().collect())
+                        .unwrap_or_default(),
+                    replies: self.get_topic_replies(id).await?,
+                });
+            }
+            if page >= json["pages"].as_i64().unwrap_or(1) {
+                break;
+            }
+            page += 1;
+        }
+        Ok(topics)
+    }
+
+    // the first page is enough, the poller only looks for new ones
+    pub async fn get_course_announcements(
+        &self,
+        course_id: i64,
+    ) -> Result<Vec<CourseAnnouncement>> {
+        if !self.have_login {
+            return Err(anyhow!("Not login"));
+        }
+        let res = self
+            .get(format!(
+                "https://courses.zju.edu.cn/api/courses/{}/announcements?page=1&page_size=20",
+                course_id
+            ))
+            .send()
+            .await?;
+        let json: Value = res.json().await?;
+        let mut announcements = Vec::new();
+        for announcement in json["announcements"].as_array().unwrap_or(&Vec::new()) {
+            let id = match announcement["id"].as_i64() {
+                Some(id) => id,
+                None => continue,
+            };
+            announcements.push(CourseAnnouncement {
+                id,
+                course_id,
+                course_name: "".to_string(), // course name will be set by caller
+                title: announcement["title"].as_str().unwrap_or("").to_string(),
+                content: announcement["content"].as_str().unwrap_or("").to_string(),
+                created_at: announcement["created_at"].as_str().unwrap_or("").to_string(),
+            });
+        }
+        Ok(announcements)
+    }
+
     pub async fn download_file(&self, id: i64, reference_id: i64, name: &str, path: &str) -> Result<()> {
         let res = self
             .get(format!(
@@ -961,6 +1459,50 @@ impl ZjuAssist {
         Ok(result == "1")
     }
 
+    // courses of the current evaluation round with their submit state
+    pub async fn get_evaluation_courses(&mut self) -> Result<Vec<EvaluationCourse>> {
+        let nd = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            .to_string();
+        let data = Self::zdbk_query_data(&nd);
+        let json = self
+            .post_zdbk(
+                format!(
+                    "https://zdbk.zju.edu.cn/jwglxt/xspjgl/xspj_cxXspjIndex.html?doType=query&gnmkdm=N401605&su={}",
+                    self.username
+                ),
+                &data,
+            )
+            .await?;
+
+        Ok(json["items"]
+            .as_array()
+            .unwrap_or(&Vec::new())
+            .iter()
+            .map(parse_evaluation_course)
+            .collect())
+    }
+
+    // the window is only shown on the evaluation page, None if not announced
+    pub async fn get_evaluation_window(
+        &self,
+    ) -> Result<Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)>> {
+        if !self.have_login {
+            return Err(anyhow!("Not login"));
+        }
+        let res = self
+            .get(format!(
+                "https://zdbk.zju.edu.cn/jwglxt/xspjgl/xspj_cxXspjIndex.html?gnmkdm=N401605&layout=default&su={}",
+                self.username
+            ))
+            .send()
+            .await?;
+        let text = res.text().await?;
+        Ok(parse_evaluation_window(&text))
+    }
+
     pub async fn get_score(&mut self) -> Result<Vec<Value>> {
         let data = [
             ("xn", ""),
@@ -1011,6 +1553,231 @@ impl ZjuAssist {
         return Ok(score.iter().cloned().collect());
     }
 
+    // zdbk answers with a login page instead of json once the session expires
+    async fn post_zdbk(&mut self, url: String, data: &[(&str, &str)]) -> Result<Value> {
+        if !self.have_login {
+            return Err(anyhow!("Not login"));
+        }
+        let res = self.post(url.clone()).form(data).send().await?;
+        let text = res.text().await?;
+        if let Some(json) = serde_json::from_str::<Value>(&text).ok() {
+            return Ok(json);
+        }
+
+        self.relogin().await?;
+        let res = self.post(url).form(data).send().await?;
+        let text = res.text().await?;
+        debug!("{}", text);
+        serde_json::from_str::<Value>(&text).map_err(|_| anyhow!("zdbk request failed"))
+    }
+
+    fn parse_exam(item: &Value, kind: ExamKind) -> Option<Exam> {
+        let (time_key, location_key, seat_key) = match kind {
+            ExamKind::Midterm => ("qzkssj", "qzjsmc", "qzzwxh"),
+            ExamKind::Final => ("kssj", "jsmc", "zwxh"),
+        };
+        let text = |key: &str| {
+            item[key]
+                .as_str()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+        let time = text(time_key);
+        let location = text(location_key);
+        let seat = text(seat_key);
+        // most courses have no midterm exam
+        if kind == ExamKind::Midterm && time.is_none() && location.is_none() {
+            return None;
+        }
+        let course_code = text("xkkh")?;
+        let (start_time, end_time) = time
+            .as_deref()
+            .and_then(parse_exam_time)
+            .map(|(start, end)| (start.to_rfc3339(), end.to_rfc3339()))
+            .unzip();
+        Some(Exam {
+            id: format!(
+                "{}-{}",
+                course_code,
+                match kind {
+                    ExamKind::Midterm => "midterm",
+                    ExamKind::Final => "final",
+                }
+            ),
+            course_code,
+            course_name: text("kcmc").unwrap_or_default(),
+            credit: text("xf").and_then(|xf| xf.parse().ok()),
+            semester: format!(
+                "{} {}",
+                text("xnm").unwrap_or_default(),
+                text("xqmmc").unwrap_or_default()
+            )
+            .trim()
+            .to_string(),
+            kind,
+            time,
+            start_time,
+            end_time,
+            location,
+            seat,
+        })
+    }
+
+    // year like "2024-2025" and term like "1|秋", empty for all semesters
+    pub async fn get_exams(&mut self, year: &str, term: &str) -> Result<Vec<Exam>> {
+        let nd = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            .to_string();
+        let mut data = vec![("xnm", year), ("xqm", term)];
+        data.extend(Self::zdbk_query_data(&nd));
+        let json = self
+            .post_zdbk(
+                format!(
+                    "https://zdbk.zju.edu.cn/jwglxt/xskscx/kscx_cxXsgrksIndex.html?doType=query&gnmkdm=N509070&su={}",
+                    self.username
+                ),
+                &data,
+            )
+            .await?;
+
+        let mut exams = Vec::new();
+        for item in json["items"].as_array().unwrap_or(&Vec::new()) {
+            exams.extend(Self::parse_exam(item, ExamKind::Midterm));
+            exams.extend(Self::parse_exam(item, ExamKind::Final));
+        }
+        Ok(exams)
+    }
+
+    fn zdbk_query_data(nd: &str) -> [(&str, &str); 7] {
+        [
+            ("_search", "false"),
+            ("nd", nd),
+            ("queryModel.showCount", "5000"),
+            ("queryModel.currentPage", "1"),
+            ("queryModel.sortName", ""),
+            ("queryModel.sortOrder", "asc"),
+            ("time", "0"),
+        ]
+    }
+
+    // credit requirements per category and the courses of the student's own plan
+    pub async fn get_training_plan(&mut self) -> Result<TrainingPlan> {
+        let nd = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            .to_string();
+        let data = Self::zdbk_query_data(&nd);
+        let json = self
+            .post_zdbk(
+                format!(
+                    "https://zdbk.zju.edu.cn/jwglxt/pyfagl/pyfaxxck_cxXfyqxx.html?doType=query&gnmkdm=N153540&su={}",
+                    self.username
+                ),
+                &data,
+            )
+            .await?;
+        let categories = json["items"]
+            .as_array()
+            .unwrap_or(&Vec::new())
+            .iter()
+            .filter_map(parse_plan_category)
+            .collect();
+
+        let json = self
+            .post_zdbk(
+                format!(
+                    "https://zdbk.zju.edu.cn/jwglxt/pyfagl/pyfaxxck_cxKcxx.html?doType=query&gnmkdm=N153540&su={}",
+                    self.username
+                ),
+                &data,
+            )
+            .await?;
+        let courses = json["items"]
+            .as_array()
+            .unwrap_or(&Vec::new())
+            .iter()
+            .filter_map(parse_plan_course)
+            .collect();
+
+        Ok(TrainingPlan {
+            categories,
+            courses,
+        })
+    }
+
+    // courses zdbk has already credited to a plan category
+    pub async fn get_plan_completion(&mut self) -> Result<Vec<PlanCourse>> {
+        let nd = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            .to_string();
+        let json = self
+            .post_zdbk(
+                format!(
+                    "https://zdbk.zju.edu.cn/jwglxt/xsxy/xsxyqk_cxXsxyqkKcxx.html?doType=query&gnmkdm=N105515&su={}",
+                    self.username
+                ),
+                &Self::zdbk_query_data(&nd),
+            )
+            .await?;
+        Ok(json["items"]
+            .as_array()
+            .unwrap_or(&Vec::new())
+            .iter()
+            .filter_map(parse_plan_course)
+            .collect())
+    }
+
+    // read only, the same query the selection page runs for a teaching class
+    pub async fn get_course_seat(&mut self, xkkh: &str) -> Result<Option<SeatInfo>> {
+        let nd = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            .to_string();
+        let mut data = Self::zdbk_query_data(&nd).to_vec();
+        data.push(("xkkh", xkkh));
+        let json = self
+            .post_zdbk(
+                format!(
+                    "https://zdbk.zju.edu.cn/jwglxt/xsxk/zzxkghb_cxJxbxx.html?doType=query&gnmkdm=N253530&su={}",
+                    self.username
+                ),
+                &data,
+            )
+            .await?;
+
+        Ok(json["items"]
+            .as_array()
+            .unwrap_or(&Vec::new())
+            .iter()
+            .filter_map(parse_seat_info)
+            .find(|seat| seat.xkkh == xkkh))
+    }
+
+    // year like "2024-2025" and term like "1|秋"
+    pub async fn get_zdbk_timetable(
+        &mut self,
+        year: &str,
+        term: &str,
+    ) -> Result<Vec<ClassSession>> {
+        let data = [("xnm", year), ("xqm", term)];
+        let json = self
+            .post_zdbk(
+                format!(
+                    "https://zdbk.zju.edu.cn/jwglxt/kbcx/xskbcx_cxXsKb.html?gnmkdm=N253508&su={}",
+                    self.username
+                ),
+                &data,
+            )
+            .await?;
+        Ok(parse_zdbk_timetable(&json))
+    }
+
     pub async fn get_subtitle(&self, sub_id: i64) -> Result<Vec<SubtitleContent>> {
         let url = format!(
             "https://yjapi.cmc.zju.edu.cn/courseapi/v3/web-socket/search-trans-result?sub_id={}&format=json",
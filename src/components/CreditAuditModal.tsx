@@ -0,0 +1,113 @@
+import React, { useEffect, useState } from 'react';
+import { Modal, Table, Progress, Typography, Collapse, App } from 'antd';
+import { invoke } from '@tauri-apps/api/core';
+import { CategoryAudit, CreditAudit, ScoreRecord } from '../model';
+
+const { Text } = Typography
+
+interface CreditAuditModalProps {
+  open: boolean;
+  onCancel: () => void;
+}
+
+const courseColumns = [
+  { title: '课程代码', dataIndex: 'course_code', width: 120 },
+  { title: '课程名称', dataIndex: 'course_name' },
+  { title: '学分', dataIndex: 'credit', width: 65 },
+  { title: '成绩', dataIndex: 'score', width: 65 },
+]
+
+export default function CreditAuditModal({ open, onCancel }: CreditAuditModalProps) {
+  const { notification } = App.useApp()
+
+  const [audit, setAudit] = useState<CreditAudit | null>(null)
+  const [loading, setLoading] = useState(false)
+
+  useEffect(() => {
+    if (!open) return
+    setLoading(true)
+    invoke<CreditAudit>('get_credit_audit').then((res) => {
+      setAudit(res)
+    }).catch((err) => {
+      notification.error({
+        message: '获取培养方案失败',
+        description: String(err)
+      })
+    }).finally(() => {
+      setLoading(false)
+    })
+  }, [open])
+
+  const columns = [
+    { title: '类别', dataIndex: 'name' },
+    { title: '要求学分', dataIndex: 'required_credit', width: 90 },
+    {
+      title: '已修学分', dataIndex: 'completed_credit', width: 90,
+      render: (credit: number) => credit.toFixed(1)
+    },
+    {
+      title: '还需学分', dataIndex: 'remaining_credit', width: 90,
+      render: (credit: number) => credit > 0 ? <Text type='danger'>{credit.toFixed(1)}</Text> : '—'
+    },
+    {
+      title: '进度', width: 160,
+      render: (_: any, category: CategoryAudit) => category.required_credit > 0 ? (
+        <Progress
+          size='small'
+          percent={Math.min(100, Math.round(category.completed_credit / category.required_credit * 100))}
+        />
+      ) : null
+    },
+  ]
+
+  return (
+    <Modal
+      title="培养方案学分审核"
+      open={open}
+      onCancel={onCancel}
+      footer={null}
+      width={760}
+    >
+      <Table<CategoryAudit>
+        columns={columns}
+        dataSource={audit?.categories ?? []}
+        rowKey='name'
+        loading={loading}
+        pagination={false}
+        size='small'
+        bordered
+        expandable={{
+          rowExpandable: (category) => category.courses.length > 0,
+          expandedRowRender: (category) => (
+            <Table<ScoreRecord>
+              columns={courseColumns}
+              dataSource={category.courses}
+              rowKey='xkkh'
+              pagination={false}
+              size='small'
+            />
+          ),
+        }}
+        footer={() => audit ? `共要求 ${audit.required_credit.toFixed(1)} 学分，已计入 ${audit.completed_credit.toFixed(1)} 学分` : ''}
+      />
+      {audit && audit.unmatched.length > 0 &&
+        <Collapse
+          style={{ marginTop: 15 }}
+          items={[{
+            key: 'unmatched',
+            label: `未归入培养方案类别的课程（${audit.unmatched.length} 门）`,
+            children: (
+              <Table<ScoreRecord>
+                columns={courseColumns}
+                dataSource={audit.unmatched}
+                rowKey='xkkh'
+                pagination={false}
+                size='small'
+              />
+            ),
+          }]}
+        />
+      }
+    </Modal>
+  );
+}
@@ -0,0 +1,122 @@
+import React, { useEffect, useState } from 'react';
+import { Modal, Table, Progress, Typography, Button, Space, App } from 'antd';
+import { invoke } from '@tauri-apps/api/core';
+import { CategoryStat, GradeBucket, ScoreAnalytics, SemesterTrend } from '../model';
+import { semesterName } from '../utils';
+
+const { Title, Text } = Typography
+
+interface ScoreAnalyticsModalProps {
+  open: boolean;
+  onCancel: () => void;
+}
+
+export default function ScoreAnalyticsModal({ open, onCancel }: ScoreAnalyticsModalProps) {
+  const { notification } = App.useApp()
+
+  const [analytics, setAnalytics] = useState<ScoreAnalytics | null>(null)
+  const [loading, setLoading] = useState(false)
+
+  useEffect(() => {
+    if (!open) return
+    setLoading(true)
+    invoke<ScoreAnalytics>('get_score_analytics').then((res) => {
+      setAnalytics(res)
+    }).catch((err) => {
+      notification.error({
+        message: '获取成绩分析失败',
+        description: String(err)
+      })
+    }).finally(() => {
+      setLoading(false)
+    })
+  }, [open])
+
+  const handleExport = (format: string) => {
+    invoke('export_score_analytics', { format }).catch((err) => {
+      notification.error({
+        message: '导出成绩分析失败',
+        description: String(err)
+      })
+    })
+  }
+
+  const maxGpa = 5
+  const maxCount = Math.max(1, ...(analytics?.distribution ?? []).map(bucket => bucket.course_count))
+
+  const trendColumns = [
+    { title: '学期', dataIndex: 'semester', render: (semester: string) => semesterName(semester) },
+    {
+      title: '学期均绩', dataIndex: 'gpa', width: 180,
+      render: (gpa: number) => <Progress size='small' percent={gpa / maxGpa * 100} format={() => gpa.toFixed(2)} />
+    },
+    { title: '累计均绩', dataIndex: 'cumulative_gpa', width: 90, render: (gpa: number) => gpa.toFixed(2) },
+    { title: '获得学分', dataIndex: 'earned_credit', width: 90 },
+    { title: '累计学分', dataIndex: 'cumulative_credit', width: 90 },
+  ]
+
+  const distributionColumns = [
+    { title: '分数段', dataIndex: 'label', width: 90 },
+    {
+      title: '课程数', dataIndex: 'course_count',
+      render: (count: number) => <Progress size='small' percent={count / maxCount * 100} format={() => count} />
+    },
+    { title: '学分', dataIndex: 'credit', width: 90 },
+  ]
+
+  const categoryColumns = [
+    { title: '类别', dataIndex: 'category' },
+    { title: '课程数', width: 80, render: (_: any, stat: CategoryStat) => stat.summary.course_count },
+    { title: '学分', width: 80, render: (_: any, stat: CategoryStat) => stat.summary.credit },
+    { title: '均绩', width: 80, render: (_: any, stat: CategoryStat) => stat.summary.gpa.toFixed(2) },
+    { title: '加权均分', width: 90, render: (_: any, stat: CategoryStat) => stat.summary.average_score.toFixed(2) },
+  ]
+
+  return (
+    <Modal
+      title="成绩分析"
+      open={open}
+      onCancel={onCancel}
+      width={800}
+      footer={
+        <Space>
+          <Button disabled={!analytics} onClick={() => handleExport('json')}>导出 JSON</Button>
+          <Button disabled={!analytics} onClick={() => handleExport('csv')}>导出 CSV</Button>
+        </Space>
+      }
+    >
+      {analytics && <Text type='secondary'>数据来自 {analytics.synced_at} 同步的成绩</Text>}
+      <Title level={5}>学期趋势</Title>
+      <Table<SemesterTrend>
+        columns={trendColumns}
+        dataSource={analytics?.trend ?? []}
+        rowKey='semester'
+        loading={loading}
+        pagination={false}
+        size='small'
+        bordered
+      />
+      <Title level={5}>成绩分布</Title>
+      <Table<GradeBucket>
+        columns={distributionColumns}
+        dataSource={analytics?.distribution ?? []}
+        rowKey='label'
+        loading={loading}
+        pagination={false}
+        size='small'
+        bordered
+      />
+      <Title level={5}>课程类别</Title>
+      <Table<CategoryStat>
+        columns={categoryColumns}
+        dataSource={analytics?.categories ?? []}
+        rowKey='category'
+        loading={loading}
+        pagination={false}
+        size='small'
+        bordered
+        footer={() => '课程类别来自最近一次学分审核，未审核过的课程归为未分类'}
+      />
+    </Modal>
+  );
+}
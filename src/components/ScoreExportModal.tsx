@@ -0,0 +1,86 @@
+import React, { useState } from 'react';
+import { Modal, List, Radio, Checkbox, App } from 'antd';
+import { invoke } from '@tauri-apps/api/core';
+
+interface ScoreExportModalProps {
+  open: boolean;
+  onCancel: () => void;
+}
+
+const scoreColumns = [
+  { label: '学期', value: 'semester' },
+  { label: '选课课号', value: 'xkkh' },
+  { label: '课程代码', value: 'course_code' },
+  { label: '课程名称', value: 'course_name' },
+  { label: '成绩', value: 'score' },
+  { label: '补考成绩', value: 'makeup_score' },
+  { label: '学分', value: 'credit' },
+  { label: '绩点', value: 'grade_point' },
+  { label: '计入绩点', value: 'counted' },
+]
+
+export default function ScoreExportModal({ open, onCancel }: ScoreExportModalProps) {
+  const { notification } = App.useApp()
+
+  const [format, setFormat] = useState('xlsx')
+  const [columns, setColumns] = useState<string[]>(scoreColumns.map(column => column.value))
+  const [exporting, setExporting] = useState(false)
+
+  const handleExport = () => {
+    setExporting(true)
+    invoke('export_score', { format, columns: format === 'pdf' ? null : columns }).then(() => {
+      onCancel()
+    }).catch(err => {
+      notification.error({
+        message: '导出成绩失败',
+        description: err
+      })
+    }).finally(() => {
+      setExporting(false)
+    })
+  }
+
+  return (
+    <Modal
+      title="导出成绩"
+      open={open}
+      onOk={handleExport}
+      onCancel={onCancel}
+      okText="导出"
+      okButtonProps={{ disabled: format !== 'pdf' && columns.length === 0 }}
+      confirmLoading={exporting}
+      width={600}
+    >
+      <List>
+        <List.Item>
+          <List.Item.Meta
+            title="文件格式"
+            description="PDF 为按学期分组的成绩单，包含学期与总计的学分和均绩"
+          />
+          <Radio.Group
+            value={format}
+            onChange={(e) => setFormat(e.target.value)}
+            buttonStyle="solid"
+          >
+            <Radio.Button value="xlsx">Excel</Radio.Button>
+            <Radio.Button value="csv">CSV</Radio.Button>
+            <Radio.Button value="pdf">PDF</Radio.Button>
+          </Radio.Group>
+        </List.Item>
+
+        <List.Item>
+          <List.Item.Meta
+            title="导出列"
+            description="仅对 Excel 和 CSV 生效"
+          />
+        </List.Item>
+        <Checkbox.Group
+          options={scoreColumns}
+          value={columns}
+          disabled={format === 'pdf'}
+          onChange={(checkedValues) => setColumns(checkedValues as string[])}
+        />
+      </List>
+    </Modal>
+  );
+}
@@ -0,0 +1,134 @@
+import React, { useEffect, useState } from 'react';
+import { App, Modal, Form, Input, InputNumber, Select, Switch, Checkbox, Button, Space } from 'antd';
+import { invoke } from '@tauri-apps/api/core';
+import { useConfig } from '../../context/ConfigContext';
+import { Config } from '../../model';
+
+interface EmailSettingsModalProps {
+  open: boolean;
+  onCancel: () => void;
+}
+
+type EmailSettings = Pick<Config, 'smtp_enabled' | 'smtp_host' | 'smtp_port' | 'smtp_security' | 'smtp_username' | 'smtp_password' | 'smtp_from' | 'smtp_to' | 'smtp_events' | 'digest_frequency' | 'digest_hour'>
+
+export default function EmailSettingsModal({ open, onCancel }: EmailSettingsModalProps) {
+  const { config, updateConfigBatch } = useConfig();
+  const { message } = App.useApp();
+  const [form] = Form.useForm<EmailSettings>();
+  const [testing, setTesting] = useState(false);
+  const [sendingDigest, setSendingDigest] = useState(false);
+
+  useEffect(() => {
+    if (open && config) {
+      form.setFieldsValue({
+        smtp_enabled: config.smtp_enabled,
+        smtp_host: config.smtp_host,
+        smtp_port: config.smtp_port,
+        smtp_security: config.smtp_security,
+        smtp_username: config.smtp_username,
+        smtp_password: config.smtp_password,
+        smtp_from: config.smtp_from,
+        smtp_to: config.smtp_to,
+        smtp_events: config.smtp_events,
+        digest_frequency: config.digest_frequency,
+        digest_hour: config.digest_hour,
+      });
+    }
+  }, [open, config, form]);
+
+  const save = () => updateConfigBatch(form.getFieldsValue());
+
+  const handleOk = () => {
+    save().then(() => onCancel()).catch(() => { });
+  }
+
+  // both commands read the saved config, so save first
+  const handleTest = () => {
+    setTesting(true);
+    save().then(() => invoke('test_email')).then(() => {
+      message.success('测试邮件已发送');
+    }).catch((err) => {
+      message.error(`发送失败: ${err}`);
+    }).finally(() => setTesting(false));
+  }
+
+  const handleSendDigest = () => {
+    setSendingDigest(true);
+    save().then(() => invoke('send_digest_now')).then(() => {
+      message.success('摘要邮件已发送');
+    }).catch((err) => {
+      message.error(`发送失败: ${err}`);
+    }).finally(() => setSendingDigest(false));
+  }
+
+  return (
+    <Modal
+      title="邮件通知"
+      open={open}
+      onOk={handleOk}
+      onCancel={onCancel}
+      width={600}
+    >
+      <div style={{ maxHeight: '60vh', overflowY: 'auto', paddingRight: 10 }}>
+        <Form layout="vertical" form={form}>
+          <Form.Item label="启用邮件通知" name="smtp_enabled" valuePropName="checked">
+            <Switch />
+          </Form.Item>
+          <Space.Compact style={{ width: '100%' }}>
+            <Form.Item label="SMTP 服务器" name="smtp_host" style={{ flex: 1 }}>
+              <Input placeholder="例如 smtp.zju.edu.cn" />
+            </Form.Item>
+            <Form.Item label="端口" name="smtp_port">
+              <InputNumber min={1} max={65535} />
+            </Form.Item>
+            <Form.Item label="加密方式" name="smtp_security">
+              <Select style={{ width: 120 }} options={[
+                { value: 'tls', label: 'SSL/TLS' },
+                { value: 'starttls', label: 'STARTTLS' },
+                { value: 'none', label: '不加密' },
+              ]} />
+            </Form.Item>
+          </Space.Compact>
+          <Form.Item label="用户名" name="smtp_username">
+            <Input placeholder="通常为完整的邮箱地址" />
+          </Form.Item>
+          <Form.Item label="密码" name="smtp_password">
+            <Input.Password placeholder="密码或客户端授权码" />
+          </Form.Item>
+          <Form.Item label="发件人" name="smtp_from" tooltip="留空则使用用户名">
+            <Input />
+          </Form.Item>
+          <Form.Item label="收件人" name="smtp_to" tooltip="多个地址以逗号分隔，留空则发送给发件人">
+            <Input />
+          </Form.Item>
+          <Form.Item label="即时提醒" name="smtp_events">
+            <Checkbox.Group options={[
+              { label: '成绩', value: 'score' },
+              { label: '待办', value: 'todo' },
+              { label: '考试', value: 'exam' },
+              { label: '课件下载', value: 'download' },
+              { label: '教学评价', value: 'evaluation' },
+              { label: '选课余量', value: 'seat' },
+            ]} />
+          </Form.Item>
+          <Space.Compact style={{ width: '100%' }}>
+            <Form.Item label="摘要邮件" name="digest_frequency" tooltip="汇总即将截止的待办事项、课件同步发现的新课件和新的课程回放" style={{ flex: 1 }}>
+              <Select options={[
+                { value: 'off', label: '不发送' },
+                { value: 'daily', label: '每天' },
+                { value: 'weekly', label: '每周一' },
+              ]} />
+            </Form.Item>
+            <Form.Item label="发送时间（时）" name="digest_hour">
+              <InputNumber min={0} max={23} />
+            </Form.Item>
+          </Space.Compact>
+          <Space>
+            <Button loading={testing} onClick={handleTest}>发送测试邮件</Button>
+            <Button loading={sendingDigest} onClick={handleSendDigest}>立即发送摘要</Button>
+          </Space>
+        </Form>
+      </div>
+    </Modal>
+  )
+}
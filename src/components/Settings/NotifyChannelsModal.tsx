@@ -0,0 +1,136 @@
+import React, { useEffect, useState } from 'react';
+import { App, Modal, List, Select, Input, Switch, Checkbox, Button, Space, Tooltip, Typography } from 'antd';
+import { PlusOutlined, DeleteOutlined, SendOutlined } from '@ant-design/icons';
+import { invoke } from '@tauri-apps/api/core';
+import { useConfig } from '../../context/ConfigContext';
+import { NotifyChannel, NotifyEvent } from '../../model';
+
+const { Text } = Typography;
+
+interface NotifyChannelsModalProps {
+  open: boolean;
+  onCancel: () => void;
+}
+
+// which fields each channel needs, url of channels with a public server is optional
+const channelKinds: Record<string, { label: string; url?: string; token?: string; target?: string }> = {
+  feishu: { label: '飞书 / Lark', url: '机器人 Webhook' },
+  wecom: { label: '企业微信', url: '机器人 Webhook' },
+  ding: { label: '钉钉', url: '机器人 Webhook', token: '加签密钥（可选）' },
+  telegram: { label: 'Telegram', url: 'Bot API 地址（可选）', token: 'Bot Token', target: 'Chat ID' },
+  bark: { label: 'Bark', url: '服务器地址（可选）', token: 'Device Key' },
+  serverchan: { label: 'Server 酱', url: '服务器地址（可选）', token: 'SendKey' },
+  ntfy: { label: 'ntfy', url: '服务器地址（可选）', token: 'Access Token（可选）', target: 'Topic' },
+  gotify: { label: 'Gotify', url: '服务器地址', token: 'App Token' },
+};
+
+const eventOptions: { label: string; value: NotifyEvent }[] = [
+  { label: '成绩', value: 'score' },
+  { label: '待办', value: 'todo' },
+  { label: '考试', value: 'exam' },
+  { label: '课件下载', value: 'download' },
+  { label: '课程通知', value: 'announcement' },
+  { label: '教学评价', value: 'evaluation' },
+  { label: '选课余量', value: 'seat' },
+];
+
+export default function NotifyChannelsModal({ open, onCancel }: NotifyChannelsModalProps) {
+  const { config, updateConfigField } = useConfig();
+  const { message } = App.useApp();
+  const [channels, setChannels] = useState<NotifyChannel[]>([]);
+
+  // edit a copy and save it once on confirm
+  useEffect(() => {
+    if (open) {
+      setChannels(config.notify_channels || []);
+    }
+  }, [open, config]);
+
+  const updateChannel = (index: number, updates: Partial<NotifyChannel>) => {
+    setChannels(channels.map((channel, i) => i === index ? { ...channel, ...updates } : channel));
+  }
+
+  const addChannel = () => {
+    setChannels([...channels, {
+      name: `通知渠道 ${channels.length + 1}`,
+      kind: 'feishu',
+      url: '',
+      token: '',
+      target: '',
+      enabled: true,
+      events: ['score', 'todo', 'exam'],
+    }]);
+  }
+
+  const removeChannel = (index: number) => {
+    setChannels(channels.filter((_, i) => i !== index));
+  }
+
+  const handleOk = () => {
+    updateConfigField('notify_channels', channels).then(() => onCancel()).catch(() => { });
+  }
+
+  const testChannel = (channel: NotifyChannel) => {
+    invoke('test_notify_channel', { channel }).then(() => {
+      message.success('测试消息已发送');
+    }).catch((err) => {
+      message.error(`发送失败: ${err}`);
+    })
+  }
+
+  return (
+    <Modal
+      title="通知渠道"
+      open={open}
+      onCancel={onCancel}
+      width={640}
+      footer={[
+        <Button key="add" icon={<PlusOutlined />} onClick={addChannel}>
+          添加渠道
+        </Button>,
+        <Button key="confirm" type="primary" onClick={handleOk}>
+          确定
+        </Button>
+      ]}
+    >
+      <div style={{ maxHeight: '60vh', overflowY: 'auto', paddingRight: 10 }}>
+        <List
+          dataSource={channels}
+          locale={{ emptyText: '尚未添加通知渠道，钉钉机器人 Webhook 仍会收到成绩通知' }}
+          renderItem={(channel, index) => {
+            const kind = channelKinds[channel.kind] || { label: channel.kind };
+            return (
+              <List.Item>
+                <Space direction="vertical" style={{ width: '100%' }}>
+                  <Space.Compact style={{ width: '100%' }}>
+                    <Select
+                      style={{ width: 140 }}
+                      value={channel.kind}
+                      options={Object.entries(channelKinds).map(([value, { label }]) => ({ value, label }))}
+                      onChange={(value) => updateChannel(index, { kind: value })}
+                    />
+                    <Input value={channel.name} placeholder="名称" onChange={(e) => updateChannel(index, { name: e.target.value })} />
+                    <Button icon={<Tooltip title='发送测试消息'><SendOutlined /></Tooltip>} onClick={() => testChannel(channel)} />
+                    <Button danger icon={<Tooltip title='删除'><DeleteOutlined /></Tooltip>} onClick={() => removeChannel(index)} />
+                  </Space.Compact>
+                  {kind.url && <Input placeholder={kind.url} value={channel.url} onChange={(e) => updateChannel(index, { url: e.target.value })} />}
+                  {kind.token && <Input.Password placeholder={kind.token} value={channel.token} onChange={(e) => updateChannel(index, { token: e.target.value })} />}
+                  {kind.target && <Input placeholder={kind.target} value={channel.target} onChange={(e) => updateChannel(index, { target: e.target.value })} />}
+                  <Space>
+                    <Switch size="small" checked={channel.enabled} onChange={(checked) => updateChannel(index, { enabled: checked })} />
+                    <Text type="secondary" style={{ fontSize: 12 }}>推送：</Text>
+                    <Checkbox.Group
+                      options={eventOptions}
+                      value={channel.events}
+                      onChange={(values) => updateChannel(index, { events: values as NotifyEvent[] })}
+                    />
+                  </Space>
+                </Space>
+              </List.Item>
+            )
+          }}
+        />
+      </div>
+    </Modal>
+  )
+}
@@ -0,0 +1,89 @@
+import React, { useEffect, useState } from 'react';
+import { App, Modal, Form, Select, InputNumber, Switch, Button, Table } from 'antd';
+import { invoke } from '@tauri-apps/api/core';
+import { useConfig } from '../../context/ConfigContext';
+import { Config, SeatInfo } from '../../model';
+
+interface SeatWatchModalProps {
+  open: boolean;
+  onCancel: () => void;
+}
+
+type SeatWatchSettings = Pick<Config, 'seat_watch' | 'seat_watch_xkkhs' | 'seat_poll_interval'>
+
+const seatColumns = [
+  { title: '选课课号', dataIndex: 'xkkh' },
+  { title: '课程名称', dataIndex: 'course_name' },
+  { title: '教师', dataIndex: 'teacher', width: 90 },
+  {
+    title: '已选/容量', width: 90,
+    render: (_: any, seat: SeatInfo) => `${seat.enrolled}/${seat.capacity}`
+  },
+]
+
+export default function SeatWatchModal({ open, onCancel }: SeatWatchModalProps) {
+  const { config, updateConfigBatch } = useConfig();
+  const { message } = App.useApp();
+  const [form] = Form.useForm<SeatWatchSettings>();
+  const [seats, setSeats] = useState<SeatInfo[]>([]);
+  const [checking, setChecking] = useState(false);
+
+  useEffect(() => {
+    if (open && config) {
+      form.setFieldsValue({
+        seat_watch: config.seat_watch,
+        seat_watch_xkkhs: config.seat_watch_xkkhs,
+        seat_poll_interval: config.seat_poll_interval,
+      });
+      invoke<SeatInfo[]>('get_seat_snapshot').then(setSeats).catch(() => { });
+    }
+  }, [open, config, form]);
+
+  const save = () => updateConfigBatch(form.getFieldsValue());
+
+  const handleOk = () => {
+    save().then(() => onCancel()).catch(() => { });
+  }
+
+  // the command reads the saved list, so save first
+  const handleCheck = () => {
+    setChecking(true);
+    save().then(() => invoke<SeatInfo[]>('check_seats_now')).then((res) => {
+      setSeats(res);
+    }).catch((err) => {
+      message.error(`查询失败: ${err}`);
+    }).finally(() => setChecking(false));
+  }
+
+  return (
+    <Modal
+      title="选课余量监控"
+      open={open}
+      onOk={handleOk}
+      onCancel={onCancel}
+      width={650}
+    >
+      <Form layout="vertical" form={form}>
+        <Form.Item label="启用监控" name="seat_watch" valuePropName="checked">
+          <Switch />
+        </Form.Item>
+        <Form.Item label="选课课号" name="seat_watch_xkkhs" tooltip="输入后回车添加，例如 (2024-2025-2)-211G0200-0001">
+          <Select mode="tags" open={false} tokenSeparators={[',', ' ', '，']} placeholder="要监控的教学班选课课号" />
+        </Form.Item>
+        <Form.Item label="查询间隔（秒）" name="seat_poll_interval" tooltip="为避免频繁访问教务系统，间隔不能少于 30 秒，查询失败时会自动延长间隔">
+          <InputNumber min={30} max={3600} changeOnWheel />
+        </Form.Item>
+      </Form>
+      <Table<SeatInfo>
+        columns={seatColumns}
+        dataSource={seats}
+        rowKey='xkkh'
+        pagination={false}
+        size='small'
+        bordered
+        locale={{ emptyText: '暂无查询结果' }}
+      />
+      <Button style={{ marginTop: 10 }} loading={checking} onClick={handleCheck}>立即查询</Button>
+    </Modal>
+  );
+}
@@ -6,6 +6,9 @@ import * as dialog from "@tauri-apps/plugin-dialog";
 import { useConfig } from '../../context/ConfigContext';
 import LlmSettingsModal from './LlmSettingsModal';
 import SubtitleSettingsModal from './SubtitleSettingsModal';
+import NotifyChannelsModal from './NotifyChannelsModal';
+import EmailSettingsModal from './EmailSettingsModal';
+import SeatWatchModal from './SeatWatchModal';
 import { Config, VersionInfo } from '../../model';
 
 const { Text } = Typography;
@@ -28,14 +31,19 @@ export default function Settings({
   const { config, updateConfigField, updateConfigBatch } = useConfig();
   const { notification } = App.useApp();
   const [dingUrlInput, setDingUrlInput] = useState('');
+  const [dingSecretInput, setDingSecretInput] = useState('');
   const [subtitleModalOpen, setSubtitleModalOpen] = useState(false);
   const [llmModalOpen, setLlmModalOpen] = useState(false);
+  const [notifyModalOpen, setNotifyModalOpen] = useState(false);
+  const [emailModalOpen, setEmailModalOpen] = useState(false);
+  const [seatModalOpen, setSeatModalOpen] = useState(false);
   const [isEnablingLlm, setIsEnablingLlm] = useState(false);
 
   // 当 Drawer 打开或配置更新时，同步钉钉 URL 到输入框
   useEffect(() => {
     if (open && config) {
       setDingUrlInput(config.ding_url || '');
+      setDingSecretInput(config.ding_secret || '');
     }
   }, [open, config]);
 
@@ -63,7 +71,8 @@ export default function Settings({
       oldTotalCredit: 37,
       totalGp: 5,
       totalCredit: 40,
-      dingUrl: dingUrlInput
+      dingUrl: dingUrlInput,
+      dingSecret: dingSecretInput
     }).catch((err) => {
       notification.error({
         message: '发送通知失败',
@@ -73,7 +82,7 @@ export default function Settings({
   }
 
   const handleSaveDingUrl = () => {
-    updateConfigField('ding_url', dingUrlInput);
+    updateConfigBatch({ ding_url: dingUrlInput, ding_secret: dingSecretInput });
     notification.success({ message: '钉钉 Webhook 已保存' });
   }
 
@@ -202,17 +211,28 @@ export default function Settings({
               title={<Text style={{ fontWeight: 'normal' }}>钉钉机器人 Webhook</Text>}
               description={
                 <div>
-                  <Text type="secondary" style={{ fontWeight: 'normal', fontSize: 12 }}>检测到成绩更新后，将使用以下钉钉机器人 Webhook 发送通知。若留空，则不使用钉钉机器人发送通知。</Text>
+                  <Text type="secondary" style={{ fontWeight: 'normal', fontSize: 12 }}>检测到成绩更新后，将使用以下钉钉机器人 Webhook 发送通知。若留空，则不使用钉钉机器人发送通知。飞书、企业微信、Telegram 等其他渠道请在<a onClick={() => setNotifyModalOpen(true)}>通知渠道</a>中配置，邮件提醒和摘要请在<a onClick={() => setEmailModalOpen(true)}>邮件通知</a>中配置。</Text>
                   <Space.Compact style={{ marginTop: 10, width: '100%' }}>
                     <Input placeholder='输入完整的钉钉机器人 Webhook' value={dingUrlInput} onChange={(e) => setDingUrlInput(e.target.value)} />
                     <Button icon={<Tooltip title='发送测试消息'><SendOutlined /></Tooltip>} onClick={handleTestDing} />
                     <Button icon={<Tooltip title='保存'><CheckOutlined /></Tooltip>} onClick={handleSaveDingUrl} />
                   </Space.Compact>
+                  <Input.Password style={{ marginTop: 10 }} placeholder='加签密钥（可选），以 SEC 开头' value={dingSecretInput} onChange={(e) => setDingSecretInput(e.target.value)} />
                 </div>
               }
             />
           </List.Item>
 
+          <List.Item>
+            <List.Item.Meta
+              title={<Text style={{ fontWeight: 'normal' }}>选课余量监控</Text>}
+              description={<div>
+                <Text type="secondary" style={{ fontWeight: 'normal', fontSize: 12 }}>选课期间定时查询指定教学班的容量和已选人数，有变化时立即通知。请在<a onClick={() => setSeatModalOpen(true)}>监控设置</a>中添加选课课号。</Text>
+              </div>}
+            />
+            <Switch checked={config.seat_watch} onChange={(checked) => updateConfigField('seat_watch', checked)} />
+          </List.Item>
+
           <List.Item>
             <List.Item.Meta
               title={<Text style={{ fontWeight: 'normal' }}>下载开始时显示下载列表</Text>}
@@ -278,6 +298,11 @@ export default function Settings({
 
       <SubtitleSettingsModal open={subtitleModalOpen} onCancel={() => setSubtitleModalOpen(false)} />
 
+      <NotifyChannelsModal open={notifyModalOpen} onCancel={() => setNotifyModalOpen(false)} />
+
+      <EmailSettingsModal open={emailModalOpen} onCancel={() => setEmailModalOpen(false)} />
+      <SeatWatchModal open={seatModalOpen} onCancel={() => setSeatModalOpen(false)} />
+
       <LlmSettingsModal
         open={llmModalOpen}
         onCancel={handleLlmModalCancel}
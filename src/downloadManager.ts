@@ -1,6 +1,6 @@
 import { invoke } from "@tauri-apps/api/core"
 import { bytesToSize, formatTime } from "./utils"
-import { Progress, Subject, Upload } from "./model"
+import { Progress, Subject, Upload, VideoActivity } from "./model"
 
 export class Task {
     id: string
@@ -201,6 +201,45 @@ export class ClassroomTask extends Task {
     }
 }
 
+export class VideoTask extends Task {
+    video: VideoActivity
+    syncUpload: boolean
+
+    constructor(video: VideoActivity, syncUpload: boolean = false) {
+        super()
+        this.id = `${video.id}-${video.path}-${video.file_name}`
+        this.name = video.file_name
+        this.path = video.path
+        this.video = video
+        this.syncUpload = syncUpload
+        this.totalSize = video.size
+    }
+
+    async start(): Promise<any> {
+        super.start()
+        // start downloading
+        return invoke('start_download_video', { id: this.id, video: this.video, syncUpload: this.syncUpload })
+    }
+
+    async cancel(): Promise<any> {
+        super.cancel()
+        // cancel downloading
+        return invoke('cancel_download', { id: this.id })
+    }
+
+    async openFile(folder: boolean): Promise<string> {
+        return invoke('open_file', { path: `${this.video.path}/${this.video.file_name}`, folder })
+    }
+
+    public equals(task: Task): boolean {
+        if (task instanceof VideoTask) {
+            return this.id === task.id && this.name === task.name && this.path === task.path
+        } else {
+            return false
+        }
+    }
+}
+
 export class DownloadManager {
     _maxConcurrentTasks: number
     queue: Task[]
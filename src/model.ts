@@ -26,11 +26,34 @@ export class Subject {
     ppt_image_urls: string[]
 }
 
+export class VideoActivity {
+    id: number
+    course_id: number
+    course_name: string
+    file_name: string
+    url: string
+    path: string
+    size: number
+}
+
+export type NotifyEvent = 'score' | 'todo' | 'exam' | 'download' | 'announcement' | 'evaluation' | 'seat'
+
+export interface NotifyChannel {
+    name: string
+    kind: string
+    url: string
+    token: string
+    target: string
+    enabled: boolean
+    events: NotifyEvent[]
+}
+
 export class Config {
     save_path: string
     to_pdf: boolean
     auto_download: boolean
     ding_url: string
+    ding_secret: string
     auto_open_download_list: boolean
     tray: boolean
     max_concurrent_tasks: number
@@ -48,6 +71,51 @@ export class Config {
     llm_prompt: string
     llm_hide_think_tag: boolean
 
+    todo_poll_interval: number
+    todo_reminder_offsets: number[]
+    todo_change_notify: boolean
+    ics_alarm_minutes: number[]
+
+    calendar_feed_enabled: boolean
+    calendar_feed_port: number
+    calendar_feed_token: string
+
+    caldav_enabled: boolean
+    caldav_url: string
+    caldav_username: string
+    caldav_password: string
+
+    exam_poll_interval: number
+    exam_notify: boolean
+
+    notify_channels: NotifyChannel[]
+
+    smtp_enabled: boolean
+    smtp_host: string
+    smtp_port: number
+    smtp_security: string
+    smtp_username: string
+    smtp_password: string
+    smtp_from: string
+    smtp_to: string
+    smtp_events: NotifyEvent[]
+    digest_frequency: string
+    digest_hour: number
+
+    score_watch: boolean
+    score_poll_interval: number
+
+    seat_watch: boolean
+    seat_watch_xkkhs: string[]
+    seat_poll_interval: number
+
+    evaluation_poll_interval: number
+    evaluation_remind_days: number[]
+
+    announcement_poll_interval: number
+
+    campus_period_times: Record<string, string[]>
+
     constructor(config?: Partial<Config>) {
         Object.assign(this, config);
     }
@@ -57,6 +125,93 @@ export class Config {
     }
 }
 
+export interface SeatInfo {
+    xkkh: string
+    course_name: string
+    teacher: string
+    capacity: number
+    enrolled: number
+}
+
+export interface EvaluationCourse {
+    id: string
+    course_name: string
+    teacher: string
+    done: boolean
+}
+
+export interface EvaluationStatus {
+    done: boolean
+    courses: EvaluationCourse[]
+    start_time: string | null
+    end_time: string | null
+}
+
+export interface ScoreRecord {
+    xkkh: string
+    course_code: string
+    semester: string
+    course_name: string
+    score: string
+    makeup_score: string | null
+    credit: number
+    grade_point: number
+    effective_grade_point: number
+    makeup_replaced: boolean
+    counted: boolean
+}
+
+export interface CategoryAudit {
+    name: string
+    required_credit: number
+    completed_credit: number
+    remaining_credit: number
+    courses: ScoreRecord[]
+}
+
+export interface CreditAudit {
+    categories: CategoryAudit[]
+    required_credit: number
+    completed_credit: number
+    unmatched: ScoreRecord[]
+}
+
+export interface GpaSummary {
+    course_count: number
+    credit: number
+    gpa: number
+    gpa_4_3: number
+    gpa_4_0: number
+    average_score: number
+}
+
+export interface SemesterTrend {
+    semester: string
+    gpa: number
+    average_score: number
+    cumulative_gpa: number
+    earned_credit: number
+    cumulative_credit: number
+}
+
+export interface GradeBucket {
+    label: string
+    course_count: number
+    credit: number
+}
+
+export interface CategoryStat {
+    category: string
+    summary: GpaSummary
+}
+
+export interface ScoreAnalytics {
+    synced_at: string
+    trend: SemesterTrend[]
+    distribution: GradeBucket[]
+    categories: CategoryStat[]
+}
+
 export interface VersionInfo {
     version: string;
     notes: string;
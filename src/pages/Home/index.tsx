@@ -8,10 +8,11 @@ import Classroom from '../Classroom'
 import Score from '../Score'
 import Settings from '../../components/Settings'
 import DownloadDrawer from '../../components/DownloadDrawer';
-import { LearningTask, Task } from '../../downloadManager';
+import { LearningTask, Task, VideoTask } from '../../downloadManager';
 import { listen } from '@tauri-apps/api/event';
 import { exit } from '@tauri-apps/plugin-process';
-import { Config, Upload, VersionInfo } from '../../model';
+import { Config, EvaluationStatus, Upload, VersionInfo, VideoActivity } from '../../model';
+import { videoKey } from '../../utils';
 import dayjs from 'dayjs'
 import LearningIcon from '../../assets/images/learning.ico'
 import ClassroomIcon from '../../assets/images/classroom.png'
@@ -37,6 +38,18 @@ interface ScoreItem {
   xf: string;
   jd: string;
   bkcj: string;
+  original_jd?: string;
+  makeup_replaced?: boolean;
+  counted?: boolean;
+}
+
+interface ScoreSnapshot {
+  synced_at: string;
+  score: ScoreItem[];
+  summary: {
+    credit: number;
+    gpa: number;
+  };
 }
 
 interface Course {
@@ -64,7 +77,7 @@ export default function Home({
 }: HomeProps) {
 
   const { modal, notification } = App.useApp()
-  const { config } = useConfig();
+  const { config, updateConfigField } = useConfig();
   const downloadManager = useDownloadManager();
 
   // Destructure tasks and count here
@@ -75,7 +88,6 @@ export default function Home({
 
   const [score, setScore] = useState<ScoreItem[]>([])
   const [loadingScore, setLoadingScore] = useState(false)
-  const [notifyScore, setNotifyScore] = useState(false)
   const [lastSyncScore, setLastSyncScore] = useState<string | null>(null)
   const [totalGp, setTotalGp] = useState(0)
   const [totalCredit, setTotalCredit] = useState(0)
@@ -86,11 +98,11 @@ export default function Home({
   const [selectedCourseKeys, setSelectedCourseKeys] = useState<React.Key[]>([])
   const [loadingUploadList, setLoadingUploadList] = useState(false)
   const [uploadList, setUploadList] = useState<Upload[]>([])
+  const [videoList, setVideoList] = useState<VideoActivity[]>([])
   const [selectedUploadKeys, setSelectedUploadKeys] = useState<React.Key[]>([])
   const [syncingUpload, setSyncingUpload] = useState(false)
   const [lastSyncUpload, setLastSyncUpload] = useState<string | null>(null)
 
-  const syncScoreTimer = useRef<any>(null)
   const syncUploadTimer = useRef<any>(null)
   const selectedCourseKeysRef = useRef(selectedCourseKeys)
   const configRef = useRef(config)
@@ -106,105 +118,37 @@ export default function Home({
     downloadManager.maxConcurrentTasks = config.max_concurrent_tasks
   }, [config, downloadManager])
 
-  function notifyUpdate(item: ScoreItem, oldTotalGp: number, oldTotalCredit: number, totalGp: number, totalCredit: number, dingUrl?: string) {
-    if (!dingUrl) {
-      dingUrl = config.ding_url
-    }
-    invoke('notify_score', {
-      score: item,
-      oldTotalGp,
-      oldTotalCredit,
-      totalGp,
-      totalCredit,
-      dingUrl
-    }).catch((err) => {
-      notification.error({
-        message: '发送通知失败',
-        description: String(err)
-      })
-    })
-  }
-
-  function updateScore(newScore: ScoreItem[]) {
-    const oldScore = score
-    setScore(newScore)
-    setLastSyncScore(dayjs().format('YYYY-MM-DD HH:mm:ss'))
-
-    let currentGp = 0
-    let currentCredit = 0
+  const notifyScore = config.score_watch
 
-    oldScore.forEach((item) => {
-      if (item.cj !== '合格' && item.cj !== '不合格' && item.cj !== '弃修') {
-        currentGp += parseFloat(item.jd) * parseFloat(item.xf)
-        currentCredit += parseFloat(item.xf)
-      }
-    })
-
-    newScore.forEach((item) => {
-      const oldItem = oldScore.find((oi) => oi.xkkh === item.xkkh)
-      if (oldItem) {
-        if (oldItem.cj !== item.cj || oldItem.bkcj !== item.bkcj || oldItem.jd !== item.jd || oldItem.xf !== item.xf) {
-          let oldTotalGp = currentGp
-          let oldTotalCredit = currentCredit
-          if (item.cj !== '合格' && item.cj !== '不合格' && item.cj !== '弃修') {
-            currentGp += parseFloat(item.jd) * parseFloat(item.xf) - parseFloat(oldItem.jd) * parseFloat(oldItem.xf)
-            currentCredit += parseFloat(item.xf) - parseFloat(oldItem.xf)
-          }
-          if (notifyScore) {
-            notifyUpdate(item, oldTotalGp, oldTotalCredit, currentGp, currentCredit)
-          }
-        }
-      } else {
-        let oldTotalGp = currentGp
-        let oldTotalCredit = currentCredit
-        if (item.cj !== '合格' && item.cj !== '不合格' && item.cj !== '弃修') {
-          currentGp += parseFloat(item.jd) * parseFloat(item.xf)
-          currentCredit += parseFloat(item.xf)
-        }
-        if (notifyScore) {
-          notifyUpdate(item, oldTotalGp, oldTotalCredit, currentGp, currentCredit)
-        }
-      }
-    })
-
-    setTotalGp(currentGp)
-    setTotalCredit(currentCredit)
+  function updateScore(snapshot: ScoreSnapshot) {
+    setScore(snapshot.score)
+    setLastSyncScore(snapshot.synced_at)
+    setTotalGp(snapshot.summary.gpa * snapshot.summary.credit)
+    setTotalCredit(snapshot.summary.credit)
   }
 
-  const startSyncScore = () => {
-    const syncScoreTask = () => {
-      setLoadingScore(true)
-      invoke<ScoreItem[]>('get_score').then((res) => {
-        updateScore(res)
-      }).catch((err) => {
-        notification.error({
-          message: '成绩同步失败',
-          description: String(err)
-        })
-      }).finally(() => {
-        const nextSync = Math.floor(Math.random() * 60000) + 60000
-        syncScoreTimer.current = setTimeout(syncScoreTask, nextSync)
-        setLoadingScore(false)
-      })
+  function describeEvaluation(evaluation: EvaluationStatus) {
+    let description = '本学期尚未完成评价，无法查询最新成绩！'
+    if (evaluation.end_time) {
+      description += `评价将于 ${dayjs(evaluation.end_time).format('MM-DD HH:mm')} 截止。`
     }
-    syncScoreTask()
-  }
-
-  const stopSyncScore = () => {
-    clearTimeout(syncScoreTimer.current)
-    syncScoreTimer.current = null
+    const pending = evaluation.courses.filter((course) => !course.done)
+    if (pending.length) {
+      description += `尚有 ${pending.length} 门课程未评价：${pending.map((course) => course.course_name).join('、')}`
+    }
+    return description
   }
 
   const handleSyncScore = () => {
     if (loadingScore) return
     setLoadingScore(true)
-    invoke<ScoreItem[]>('get_score').then((res) => {
+    invoke<ScoreSnapshot>('sync_score').then((res) => {
       updateScore(res)
-      invoke<boolean>('check_evaluation_done').then((evalDone) => {
-        if (!evalDone) {
+      invoke<EvaluationStatus>('get_evaluation_status').then((evaluation) => {
+        if (!evaluation.done) {
           notification.warning({
             message: '教学评价未完成',
-            description: '本学期尚未完成评价，无法查询最新成绩！',
+            description: describeEvaluation(evaluation),
             btn: <button onClick={() => shell.open('https://alt.zju.edu.cn/studentEvaluationBackend/list')}>去评价</button>
           })
         } else {
@@ -220,10 +164,9 @@ export default function Home({
     })
   }
 
+  // polling, diffing and notifying run in the backend
   const handleSwitchSyncScore = (checked: boolean) => {
-    setNotifyScore(checked)
-    if (checked) startSyncScore()
-    else stopSyncScore()
+    updateConfigField('score_watch', checked)
   }
 
   const syncTodoTask = () => {
@@ -271,6 +214,14 @@ export default function Home({
       downloadManager.updateProgress(res.payload)
     })
 
+    invoke<ScoreSnapshot | null>('get_score_snapshot').then((res) => {
+      if (res) updateScore(res)
+    })
+
+    const unlistenScore = listen<ScoreSnapshot>('score-updated', (res) => {
+      updateScore(res.payload)
+    })
+
     const unlistenClose = listen('close-requested', () => {
       if (!configRef.current.tray) exit(0)
     })
@@ -280,11 +231,11 @@ export default function Home({
     })
 
     return () => {
-      stopSyncScore()
       stopSyncUpload()
       // downloadManager.cleanUp() // Do NOT clean up on unmount, as manager is global singleton now
       clearInterval(syncTodoInterval)
       unlistenProgress.then((fn) => fn())
+      unlistenScore.then((fn) => fn())
       unlistenClose.then((fn) => fn())
       unlistenExportTodo.then((fn) => fn())
     }
@@ -295,10 +246,10 @@ export default function Home({
       downloadManager.cleanUp();
       invoke('logout').then(() => setIsLogin(false)).catch((err) => notification.error({ message: '退出登录失败', description: String(err) }));
     };
-    if (downloadingCount > 0 || syncingUpload || notifyScore) {
+    if (downloadingCount > 0 || syncingUpload) {
       modal.confirm({
         title: '后台服务正在运行',
-        content: '是否停止课件同步、成绩提醒、课件下载等后台服务并退出登录？',
+        content: '是否停止课件同步、课件下载等后台服务并退出登录？',
         onOk: doLogout
       })
     } else doLogout()
@@ -318,20 +269,27 @@ export default function Home({
       return
     }
     setLoadingUploadList(true)
-    invoke<Upload[]>('get_uploads_list', { courses, syncUpload: syncingUpload }).then((res) => {
+    Promise.all([
+      invoke<Upload[]>('get_uploads_list', { courses, syncUpload: syncingUpload }),
+      invoke<VideoActivity[]>('get_video_activities_list', { courses, syncUpload: syncingUpload }),
+    ]).then(([res, videos]) => {
       if (syncingUpload) {
         setLastSyncUpload(dayjs().format('YYYY-MM-DD HH:mm:ss'))
         if (config.auto_download) {
           res.forEach((item) => downloadManager.addTask(new LearningTask(item, true), true))
+          videos.forEach((video) => downloadManager.addTask(new VideoTask(video, true), true))
           setUploadList([])
+          setVideoList([])
           setSelectedUploadKeys([])
         } else {
           setUploadList(res)
-          setSelectedUploadKeys(res.map((item) => item.reference_id))
+          setVideoList(videos)
+          setSelectedUploadKeys([...res.map((item) => item.reference_id), ...videos.map(videoKey)])
         }
       } else {
         setUploadList(res)
-        setSelectedUploadKeys(res.map((item) => item.reference_id))
+        setVideoList(videos)
+        setSelectedUploadKeys([...res.map((item) => item.reference_id), ...videos.map(videoKey)])
       }
     }).catch((err) => {
       notification.error({ message: '获取课件列表失败', description: String(err) })
@@ -342,14 +300,20 @@ export default function Home({
     const syncUploadTask = () => {
       let courses = courseList.filter((item) => selectedCourseKeysRef.current.includes(item.id))
       setLoadingUploadList(true)
-      invoke<Upload[]>('get_uploads_list', { courses, syncUpload: true }).then((uploads) => {
+      Promise.all([
+        invoke<Upload[]>('get_uploads_list', { courses, syncUpload: true }),
+        invoke<VideoActivity[]>('get_video_activities_list', { courses, syncUpload: true }),
+      ]).then(([uploads, videos]) => {
         if (configRef.current.auto_download) {
           uploads.forEach((item) => downloadManager.addTask(new LearningTask(item, true), true))
+          videos.forEach((video) => downloadManager.addTask(new VideoTask(video, true), true))
           setUploadList(uploads.filter((item) => !selectedUploadKeys.includes(item.reference_id)))
+          setVideoList([])
           setSelectedUploadKeys([])
         } else {
           setUploadList(uploads)
-          setSelectedUploadKeys(uploads.map((item) => item.reference_id))
+          setVideoList(videos)
+          setSelectedUploadKeys([...uploads.map((item) => item.reference_id), ...videos.map(videoKey)])
         }
         setLastSyncUpload(dayjs().format('YYYY-MM-DD HH:mm:ss'))
       }).catch((err) => {
@@ -417,6 +381,8 @@ export default function Home({
           loadingUploadList={loadingUploadList}
           uploadList={uploadList}
           setUploadList={setUploadList}
+          videoList={videoList}
+          setVideoList={setVideoList}
           handleSwitchSync={handleSwitchSyncUpload}
           updateUploadList={updateUploadList}
           selectedUploadKeys={selectedUploadKeys}
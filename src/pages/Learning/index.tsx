@@ -3,11 +3,11 @@ import { App, Layout, Select, Button, Tooltip, Input, Row, Col, Switch, Card, Ty
 import { DownloadOutlined, ReloadOutlined } from '@ant-design/icons';
 import { invoke } from '@tauri-apps/api/core'
 import SearchTable from '../../components/SearchTable'
-import { Upload } from '../../model';
-import { bytesToSize } from '../../utils';
+import { Upload, VideoActivity } from '../../model';
+import { bytesToSize, videoKey } from '../../utils';
 import dayjs from 'dayjs'
 import { useConfig } from '../../context/ConfigContext';
-import { LearningTask, Task } from '../../downloadManager';
+import { LearningTask, Task, VideoTask } from '../../downloadManager';
 import { useAddDownloadTasks } from '../../hooks/useAddDownloadTasks';
 import { ColumnType } from 'antd/es/table';
 
@@ -40,6 +40,8 @@ interface LearningProps {
   loadingUploadList: boolean;
   uploadList: Upload[];
   setUploadList: (uploads: Upload[]) => void;
+  videoList: VideoActivity[];
+  setVideoList: (videos: VideoActivity[]) => void;
   handleSwitchSync: (checked: boolean) => void;
   updateUploadList: () => void;
   selectedUploadKeys: React.Key[];
@@ -56,6 +58,8 @@ export default function Learning({
   loadingUploadList,
   uploadList,
   setUploadList,
+  videoList,
+  setVideoList,
   handleSwitchSync,
   updateUploadList,
   selectedUploadKeys,
@@ -138,13 +142,16 @@ export default function Learning({
 
   const downloadUploads = () => {
     let uploads = uploadList.filter((item) => selectedUploadKeys.includes(item.reference_id))
-    if (uploads.length === 0) {
+    let videos = videoList.filter((video) => selectedUploadKeys.includes(videoKey(video)))
+    if (uploads.length === 0 && videos.length === 0) {
       notification.error({ message: '请选择课件' })
       return
     }
-    let tasks = uploads.map((item) => new LearningTask(item))
+    let tasks: Task[] = uploads.map((item) => new LearningTask(item))
+    tasks.push(...videos.map((video) => new VideoTask(video)))
     addDownloadTasks(tasks)
     setUploadList(uploadList.filter((item) => !selectedUploadKeys.includes(item.reference_id)))
+    setVideoList(videoList.filter((video) => !selectedUploadKeys.includes(videoKey(video))))
     setSelectedUploadKeys([])
   }
 
@@ -173,7 +180,11 @@ export default function Learning({
     setSelectedCourseKeys([])
   };
 
-  const uploadColumns: ColumnType<Upload>[] = [
+  // videos of online video activities are listed with the uploads
+  const fileList: (Upload | VideoActivity)[] = [...uploadList, ...videoList]
+  const fileKey = (item: Upload | VideoActivity) => 'reference_id' in item ? item.reference_id : videoKey(item)
+
+  const uploadColumns: ColumnType<Upload | VideoActivity>[] = [
     { title: '课程名称', dataIndex: 'course_name' },
     { title: '文件名', dataIndex: 'file_name' },
     {
@@ -257,14 +268,14 @@ export default function Learning({
           />
         </Col>
         <Col xs={14} md={15} lg={16}>
-          <SearchTable<Upload>
+          <SearchTable<Upload | VideoActivity>
             rowSelection={{
               selectedRowKeys: selectedUploadKeys,
               onChange: setSelectedUploadKeys,
             }}
-            rowKey='reference_id'
+            rowKey={fileKey}
             columns={uploadColumns}
-            dataSource={syncing && autoDownload ? [] : uploadList}
+            dataSource={syncing && autoDownload ? [] : fileList}
             loading={loadingUploadList}
             pagination={false}
             scroll={{ y: syncing ? 'calc(100vh - 292px)' : 'calc(100vh - 270px)' }}
@@ -273,10 +284,10 @@ export default function Learning({
             footer={() => syncing ? `最后同步时间：${lastSync ? lastSync : '未同步'}` : ''}
             title={() => (
               <>
-                {uploadList && uploadList.length !== 0 && (syncing && autoDownload ? '检测到新课件后将会自动下载 点击右侧立即同步👉' : <Text ellipsis={{ rows: 1, expandable: false, tooltip: true }} style={{ width: 'calc(100% - 30px)' }}>
-                  课件列表：已选择 {selectedUploadKeys.length} 个文件 共 {bytesToSize(uploadList.filter((item) => selectedUploadKeys.includes(item.reference_id)).reduce((total, item) => total + item.size, 0))}
+                {fileList.length !== 0 && (syncing && autoDownload ? '检测到新课件后将会自动下载 点击右侧立即同步👉' : <Text ellipsis={{ rows: 1, expandable: false, tooltip: true }} style={{ width: 'calc(100% - 30px)' }}>
+                  课件列表：已选择 {selectedUploadKeys.length} 个文件 共 {bytesToSize(fileList.filter((item) => selectedUploadKeys.includes(fileKey(item))).reduce((total, item) => total + item.size, 0))}
                 </Text>)}
-                {(!uploadList || uploadList.length === 0) && (syncing ? (autoDownload ? '检测到新课件后将会自动下载 点击右侧立即同步👉' : '待下载更新课件列表为空  点击右侧立即同步👉') : '课件列表为空  点击右侧刷新👉')}
+                {fileList.length === 0 && (syncing ? (autoDownload ? '检测到新课件后将会自动下载 点击右侧立即同步👉' : '待下载更新课件列表为空  点击右侧立即同步👉') : '课件列表为空  点击右侧刷新👉')}
                 <div style={{ float: 'right' }}>
                   <Tooltip title={syncing ? '立即同步' : '刷新课件列表'}>
                     <Button
@@ -1,8 +1,11 @@
 import React, { useState, useEffect } from 'react'
 import { useMediaQuery } from 'react-responsive';
 import { Button, Card, App, Typography, Switch, Tooltip } from 'antd';
-import { SyncOutlined } from '@ant-design/icons';
+import { SyncOutlined, ExportOutlined, AuditOutlined, LineChartOutlined } from '@ant-design/icons';
 import SearchTable from '../../components/SearchTable'
+import ScoreExportModal from '../../components/ScoreExportModal'
+import CreditAuditModal from '../../components/CreditAuditModal'
+import ScoreAnalyticsModal from '../../components/ScoreAnalyticsModal'
 import dayjs from 'dayjs';
 import 'dayjs/locale/zh-cn';
 import { ColumnType } from 'antd/es/table';
@@ -18,6 +21,9 @@ interface ScoreItem {
   xf: string;
   jd: string;
   bkcj: string;
+  original_jd?: string;
+  makeup_replaced?: boolean;
+  counted?: boolean;
 }
 
 interface ScoreProps {
@@ -47,6 +53,9 @@ export default function Score({
   const [selectedXkkh, setSelectedXkkh] = useState<React.Key[]>([])
   const [selectedTotalGp, setSelectedTotalGp] = useState(0)
   const [selectedTotalCredit, setSelectedTotalCredit] = useState(0)
+  const [exportOpen, setExportOpen] = useState(false)
+  const [auditOpen, setAuditOpen] = useState(false)
+  const [analyticsOpen, setAnalyticsOpen] = useState(false)
   const max770 = useMediaQuery({ query: '(max-width: 770px)' })
 
   useEffect(() => {
@@ -125,7 +134,7 @@ export default function Score({
     let gp = 0
     let credit = 0
     selectedRows.forEach(row => {
-      if (row.cj !== '合格' && row.cj !== '不合格' && row.cj !== '弃修') {
+      if (row.counted !== false && row.cj !== '合格' && row.cj !== '不合格' && row.cj !== '弃修') {
         gp += parseFloat(row.jd) * parseFloat(row.xf)
         credit += parseFloat(row.xf)
       }
@@ -149,6 +158,9 @@ export default function Score({
             </Tooltip>
           </div>
           <div style={{ display: 'flex', alignItems: 'center', flexDirection: 'row', marginLeft: 20 }}>
+            <Button icon={<LineChartOutlined />} disabled={loading || score.length === 0} onClick={() => setAnalyticsOpen(true)} style={{ marginRight: 10 }}>成绩分析</Button>
+            <Button icon={<AuditOutlined />} disabled={loading} onClick={() => setAuditOpen(true)} style={{ marginRight: 10 }}>学分审核</Button>
+            <Button icon={<ExportOutlined />} disabled={loading || score.length === 0} onClick={() => setExportOpen(true)} style={{ marginRight: 10 }}>导出</Button>
             <Button type='primary' icon={<SyncOutlined />} loading={loading} onClick={handleSync}>{loading ? '正在同步' : '立即同步'}</Button>
           </div>
         </div>
@@ -174,6 +186,9 @@ export default function Score({
         style={{ marginTop: 20 }}
         loading={loading}
       />
+      <ScoreExportModal open={exportOpen} onCancel={() => setExportOpen(false)} />
+      <CreditAuditModal open={auditOpen} onCancel={() => setAuditOpen(false)} />
+      <ScoreAnalyticsModal open={analyticsOpen} onCancel={() => setAnalyticsOpen(false)} />
     </div>
   )
 }
\ No newline at end of file
@@ -1,3 +1,5 @@
+import { VideoActivity } from './model'
+
 export function bytesToSize(bytes: number): string {
     if (bytes === 0) return '0 B';
     const k = 1024;
@@ -41,4 +43,23 @@ export function convertUrlsToMarkdown(text: string): string {
     });
 
     return newText;
-}
\ No newline at end of file
+}
+
+// 2023-2024-1 is the autumn-winter semester of that academic year
+export function semesterName(semester: string): string {
+    const index = semester.lastIndexOf('-')
+    const year = semester.slice(0, index)
+    switch (semester.slice(index + 1)) {
+        case '1':
+            return `${year} 学年秋冬学期`
+        case '2':
+            return `${year} 学年春夏学期`
+        default:
+            return semester
+    }
+}
+
+// videos share the file list with uploads, keep their keys apart from reference ids
+export function videoKey(video: VideoActivity): string {
+    return `video-${video.id}-${video.file_name}`
+}